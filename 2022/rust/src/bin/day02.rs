@@ -1,74 +1,150 @@
 use aoc2022::timer;
 use std::fs;
 
-#[derive(PartialEq, Clone)]
-enum Throw {
-    Rock,
-    Paper,
-    Sciscors,
-}
-
-#[derive(Clone)]
-enum Result {
-    Win,
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Outcome {
     Lose,
     Draw,
+    Win,
 }
 
-fn get_required_throw(theirs: &Throw, outcome: &Result) -> Throw {
-    match outcome {
-        Result::Draw => theirs.clone(),
-        Result::Lose => match theirs {
-            Throw::Sciscors => Throw::Paper,
-            Throw::Rock => Throw::Sciscors,
-            Throw::Paper => Throw::Rock,
-        },
-        Result::Win => match theirs {
-            Throw::Sciscors => Throw::Rock,
-            Throw::Rock => Throw::Paper,
-            Throw::Paper => Throw::Sciscors,
-        },
+impl Outcome {
+    /// In the order of the strategy guide's X, Y and Z.
+    const ALL: [Outcome; 3] = [Outcome::Lose, Outcome::Draw, Outcome::Win];
+
+    fn score(self) -> u32 {
+        match self {
+            Outcome::Lose => 0,
+            Outcome::Draw => 3,
+            Outcome::Win => 6,
+        }
     }
 }
 
-fn round_outcome(mine: &Throw, theirs: &Throw) -> Result {
-    if mine == theirs {
-        return Result::Draw;
+///
+/// A game where each throw beats the half of the others that precede it around
+/// a cycle, and loses to the half that follow it.
+///
+/// Throws are numbered from 0 and score one more than their number. With 3
+/// throws this is Rock (0), Paper (1), Scissors (2); with 5 it is Rock,
+/// Spock, Paper, Lizard, Scissors. There must be an odd number of throws so
+/// that every pair of different throws has a winner, and at least 3 so that
+/// every outcome is possible.
+///
+struct CyclicGame {
+    throws: usize,
+}
+
+impl CyclicGame {
+    fn new(throws: usize) -> Self {
+        assert!(
+            throws % 2 == 1 && throws >= 3,
+            "a cyclic game needs an odd number of throws, at least 3"
+        );
+        Self { throws }
     }
-    match mine {
-        Throw::Rock => {
-            if let Throw::Sciscors = theirs {
-                return Result::Win;
-            }
-        }
-        Throw::Paper => {
-            if let Throw::Rock = theirs {
-                return Result::Win;
-            }
+
+    fn outcome(&self, mine: usize, theirs: usize) -> Outcome {
+        let ahead = (mine + self.throws - theirs) % self.throws;
+        if ahead == 0 {
+            Outcome::Draw
+        } else if ahead <= self.throws / 2 {
+            Outcome::Win
+        } else {
+            Outcome::Lose
         }
-        Throw::Sciscors => {
-            if let Throw::Paper = theirs {
-                return Result::Win;
-            }
+    }
+
+    /// The throw giving the outcome against theirs. Where several throws would
+    /// do, this takes the neighbour of theirs in the cycle.
+    fn required_throw(&self, theirs: usize, outcome: Outcome) -> usize {
+        match outcome {
+            Outcome::Draw => theirs,
+            Outcome::Win => (theirs + 1) % self.throws,
+            Outcome::Lose => (theirs + self.throws - 1) % self.throws,
         }
     }
-    Result::Lose
-}
 
-fn normalize_to_result(outcome: u8, offset: u8) -> Result {
-    match outcome - offset {
-        0 => Result::Lose,
-        1 => Result::Draw,
-        2 => Result::Win,
-        _ => panic!("Unexpected outcome symbol {outcome}"),
+    fn round_score(&self, mine: usize, theirs: usize) -> u32 {
+        mine as u32 + 1 + self.outcome(mine, theirs).score()
     }
 }
-fn normalize_to_throw(throw: u8, offset: u8) -> Throw {
-    match throw - offset {
-        0 => Throw::Rock,
-        1 => Throw::Paper,
-        2 => Throw::Sciscors,
-        _ => panic!("Unexpected throw symbol {throw}"),
+
+/// What the second column of the strategy guide means.
+#[derive(Clone, Copy)]
+enum Interpretation {
+    MyThrow,
+    DesiredOutcome,
+}
+
+/// The symbols for their throws in the first column of the strategy guide.
+/// The puzzle only defines A, B and C; the rest are this repo's extension for
+/// games of more throws.
+const THEIR_SYMBOLS: &[u8] = b"ABCDEFGHI";
+
+/// The symbols for the second column of the strategy guide. The puzzle only
+/// defines X, Y and Z. For games of more throws this repo's own extension
+/// carries on with the three letters before them, then the three before those.
+const SECOND_SYMBOLS: &[u8] = b"XYZUVWRST";
+
+///
+/// The score of every line a strategy guide could hold, indexed by their throw
+/// and the second column, so that scoring a line is a single lookup.
+///
+struct ScoreTable {
+    columns: usize,
+    scores: Vec<u32>,
+}
+
+impl ScoreTable {
+    fn new(game: &CyclicGame, interpretation: Interpretation) -> Self {
+        assert!(
+            game.throws <= THEIR_SYMBOLS.len(),
+            "strategy guides only have symbols for {} throws",
+            THEIR_SYMBOLS.len()
+        );
+        let columns = match interpretation {
+            Interpretation::MyThrow => game.throws,
+            Interpretation::DesiredOutcome => Outcome::ALL.len(),
+        };
+        let scores = (0..game.throws)
+            .flat_map(|theirs| {
+                (0..columns).map(move |column| {
+                    let mine = match interpretation {
+                        Interpretation::MyThrow => column,
+                        Interpretation::DesiredOutcome => {
+                            game.required_throw(theirs, Outcome::ALL[column])
+                        }
+                    };
+                    game.round_score(mine, theirs)
+                })
+            })
+            .collect();
+        Self { columns, scores }
+    }
+
+    fn score_line(&self, line: &str) -> u32 {
+        let &[theirs, b' ', second] = line.as_bytes() else {
+            panic!("Unexpected strategy line {line:?}");
+        };
+        let rows = self.scores.len() / self.columns;
+        let theirs = THEIR_SYMBOLS[..rows]
+            .iter()
+            .position(|&s| s == theirs)
+            .unwrap_or_else(|| panic!("Unexpected throw symbol in {line:?}"));
+        let second = SECOND_SYMBOLS[..self.columns]
+            .iter()
+            .position(|&s| s == second)
+            .unwrap_or_else(|| panic!("Unexpected second column in {line:?}"));
+        self.scores[theirs * self.columns + second]
+    }
+
+    fn total_score(&self, contents: &str) -> u32 {
+        contents
+            .split('\n')
+            .take_while(|line| !line.trim().is_empty())
+            .map(|line| self.score_line(line.trim()))
+            .sum()
     }
 }
 
@@ -80,56 +156,98 @@ fn main() {
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
+    let game = CyclicGame::new(3);
+
     let mut part1_result: u32 = 0;
+    let table = ScoreTable::new(&game, Interpretation::MyThrow);
+    timer(|| part1_result = table.total_score(&contents));
+    println!("Part 1 score is {part1_result}");
+
     let mut part2_result: u32 = 0;
-    let part1 = || {
-        let mut score: u32 = 0;
-        for line in contents.split('\n') {
-            let tokens: Vec<_> = line.split_whitespace().collect();
-            if tokens.is_empty() {
-                break;
-            }
-            let theirs = normalize_to_throw(tokens[0].as_bytes()[0], b'A');
-            let mine = normalize_to_throw(tokens[1].as_bytes()[0], b'X');
-            match round_outcome(&mine, &theirs) {
-                Result::Win => score += 6,
-                Result::Draw => score += 3,
-                Result::Lose => {}
-            }
-            match mine {
-                Throw::Rock => score += 1,
-                Throw::Paper => score += 2,
-                Throw::Sciscors => score += 3,
-            }
+    let table = ScoreTable::new(&game, Interpretation::DesiredOutcome);
+    timer(|| part2_result = table.total_score(&contents));
+    println!("Part 2 score is {part2_result}");
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod test02 {
+    use super::*;
+
+    const EXAMPLE: &str = "A Y\nB X\nC Z\n";
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_scoring_both_interpretations_THEN_expected_answers_returned() {
+        let game = CyclicGame::new(3);
+        let table = ScoreTable::new(&game, Interpretation::MyThrow);
+        assert_eq!(9, table.scores.len());
+        assert_eq!(15, table.total_score(EXAMPLE));
+        let table = ScoreTable::new(&game, Interpretation::DesiredOutcome);
+        assert_eq!(12, table.total_score(EXAMPLE));
+    }
+
+    #[test]
+    fn GIVEN_rock_paper_scissors_lizard_spock_WHEN_playing_THEN_rules_hold() {
+        const ROCK: usize = 0;
+        const SPOCK: usize = 1;
+        const PAPER: usize = 2;
+        const LIZARD: usize = 3;
+        const SCISSORS: usize = 4;
+        let game = CyclicGame::new(5);
+        for (winner, loser) in [
+            (SCISSORS, PAPER),
+            (PAPER, ROCK),
+            (ROCK, LIZARD),
+            (LIZARD, SPOCK),
+            (SPOCK, SCISSORS),
+            (SCISSORS, LIZARD),
+            (LIZARD, PAPER),
+            (PAPER, SPOCK),
+            (SPOCK, ROCK),
+            (ROCK, SCISSORS),
+        ] {
+            assert_eq!(Outcome::Win, game.outcome(winner, loser));
+            assert_eq!(Outcome::Lose, game.outcome(loser, winner));
         }
-        part1_result = score;
-    };
-    timer(part1);
-    println!("Part 1 score is {part1_result}");
+        let table = ScoreTable::new(&game, Interpretation::MyThrow);
+        assert_eq!(25, table.scores.len());
+        // their Spock (B) against my Lizard (U, the fourth second symbol)
+        assert_eq!(4 + 6, table.score_line("B U"));
+    }
 
-    let part2 = || {
-        let mut score: u32 = 0;
-        for line in contents.split('\n') {
-            let tokens: Vec<_> = line.split_whitespace().collect();
-            if tokens.is_empty() {
-                break;
-            }
-            let theirs = normalize_to_throw(tokens[0].as_bytes()[0], b'A');
-            let outcome = normalize_to_result(tokens[1].as_bytes()[0], b'X');
-            match outcome {
-                Result::Win => score += 6,
-                Result::Draw => score += 3,
-                Result::Lose => {}
-            }
-            let mine = get_required_throw(&theirs, &outcome);
-            match mine {
-                Throw::Rock => score += 1,
-                Throw::Paper => score += 2,
-                Throw::Sciscors => score += 3,
+    #[test]
+    #[should_panic(expected = "Unexpected second column")]
+    fn GIVEN_symbol_past_the_alphabet_WHEN_scoring_line_THEN_panics() {
+        let table = ScoreTable::new(&CyclicGame::new(5), Interpretation::MyThrow);
+        table.score_line("B [");
+    }
+
+    #[test]
+    fn GIVEN_odd_games_WHEN_asking_for_outcomes_THEN_required_throw_delivers_them() {
+        for throws in [3, 5, 7, 9] {
+            let game = CyclicGame::new(throws);
+            for theirs in 0..throws {
+                let wins = (0..throws)
+                    .filter(|&mine| game.outcome(mine, theirs) == Outcome::Win)
+                    .count();
+                assert_eq!(throws / 2, wins);
+                for outcome in Outcome::ALL {
+                    let mine = game.required_throw(theirs, outcome);
+                    assert_eq!(outcome, game.outcome(mine, theirs));
+                }
             }
         }
-        part2_result = score;
-    };
-    timer(part2);
-    println!("Part 2 score is {part2_result}");
+    }
+
+    #[test]
+    #[should_panic]
+    fn GIVEN_even_number_of_throws_WHEN_creating_game_THEN_panics() {
+        CyclicGame::new(4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn GIVEN_single_throw_WHEN_creating_game_THEN_panics() {
+        CyclicGame::new(1);
+    }
 }
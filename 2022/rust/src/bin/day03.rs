@@ -1,28 +1,94 @@
 use aoc2022::timer;
-use std::fs;
+use aoc_common::byte_set::ByteSet;
+use std::{fmt, fs};
 
-fn get_priority(ch: u8) -> u8 {
-    if ch >= b'a' {
-        ch - b'a' + 1
-    } else {
-        ch - b'A' + 27
-    }
+#[derive(Debug, Eq, PartialEq)]
+enum RucksackError {
+    OddLength { line: usize },
+    NotAnItem { line: usize, ch: char },
+    NoDuplicate { line: usize },
+    IncompleteGroup { line: usize },
+    NoBadge { line: usize },
 }
 
-fn get_mask(ch: u8) -> u64 {
-    let position = ch % 64;
-    1 << position
+impl fmt::Display for RucksackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OddLength { line } => {
+                write!(f, "compartments can't be split evenly on line {line}")
+            }
+            Self::NotAnItem { line, ch } => write!(f, "'{ch}' is not an item on line {line}"),
+            Self::NoDuplicate { line } => write!(f, "duplicate not found on line {line}"),
+            Self::IncompleteGroup { line } => {
+                write!(f, "group starting on line {line} has fewer than 3 elves")
+            }
+            Self::NoBadge { line } => {
+                write!(f, "no badge common to the group starting on line {line}")
+            }
+        }
+    }
 }
 
-fn get_mask_for_slice<'a, I>(iter: I) -> u64
-where
-    I: std::iter::Iterator<Item = &'a u8>,
-{
-    let mut mask: u64 = 0;
-    for ch in iter {
-        mask |= get_mask(*ch);
+fn get_priority(ch: u8, line: usize) -> Result<u32, RucksackError> {
+    match ch {
+        b'a'..=b'z' => Ok((ch - b'a' + 1) as u32),
+        b'A'..=b'Z' => Ok((ch - b'A' + 27) as u32),
+        _ => Err(RucksackError::NotAnItem {
+            line,
+            ch: ch as char,
+        }),
     }
-    mask
+}
+
+/// The rucksack lines, numbered from 1, up to the first blank line.
+fn rucksacks(contents: &str) -> impl Iterator<Item = (usize, &[u8])> {
+    contents
+        .split('\n')
+        .take_while(|line| !line.is_empty())
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.as_bytes()))
+}
+
+fn part1(contents: &str) -> Result<u32, RucksackError> {
+    rucksacks(contents)
+        .map(|(line, items)| {
+            if items.len() % 2 != 0 {
+                return Err(RucksackError::OddLength { line });
+            }
+            let (first, second) = items.split_at(items.len() / 2);
+            let common = ByteSet::from_bytes(first) & ByteSet::from_bytes(second);
+            let ch = common.first().ok_or(RucksackError::NoDuplicate { line })?;
+            get_priority(ch, line)
+        })
+        .sum()
+}
+
+/// The one item type carried by all three elves of a group.
+///
+/// Each rucksack becomes a set with [`ByteSet::from_bytes`], which works
+/// through its line in chunks without branching on the items, and the three
+/// sets are combined with word-wide ANDs.
+fn group_badge(group: [&[u8]; 3]) -> Option<u8> {
+    group
+        .into_iter()
+        .map(ByteSet::from_bytes)
+        .fold(ByteSet::all(), |common, set| common & set)
+        .first()
+}
+
+fn part2(contents: &str) -> Result<u32, RucksackError> {
+    let lines = rucksacks(contents).collect::<Vec<_>>();
+    lines
+        .chunks(3)
+        .map(|chunk| {
+            let line = chunk[0].0;
+            let [(_, first), (_, second), (_, third)] = chunk else {
+                return Err(RucksackError::IncompleteGroup { line });
+            };
+            let ch = group_badge([first, second, third]).ok_or(RucksackError::NoBadge { line })?;
+            get_priority(ch, line)
+        })
+        .sum()
 }
 
 fn main() {
@@ -33,69 +99,58 @@ fn main() {
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
-    let mut part1_result: u32 = 0;
-    let part1 = || {
-        let mut score: u32 = 0;
-        for (linenumber, line) in contents.split('\n').enumerate() {
-            if line.is_empty() {
-                break;
-            }
-            // the range of ascii from 'A' to 'z' is (65 to 122)
-            // so presence can be recorded in a 64-length bitvector
-            let lineb = line.as_bytes();
-            let mask: u64 = get_mask_for_slice(lineb.iter().take(lineb.len() / 2));
-            let mut done = false;
-            for ch in lineb.iter().take(lineb.len()).skip(line.len() / 2) {
-                let position = ch % 64;
-                let flag = 1 << position;
-                if mask & flag > 0 {
-                    let pri = get_priority(*ch);
-                    score += pri as u32;
-                    done = true;
-                    break;
-                }
-            }
-            if !done {
-                panic!("duplicate not found on line {}", linenumber + 1);
-            }
-        }
-        part1_result = score;
-    };
-    timer(part1);
-    println!("Part 1 score is {part1_result}");
+    let mut part1_result = Ok(0);
+    timer(|| part1_result = part1(&contents));
+    match part1_result {
+        Ok(score) => println!("Part 1 score is {score}"),
+        Err(e) => println!("Part 1 failed: {e}"),
+    }
 
-    let mut part2_result: u32 = 0;
-    let part2 = || {
-        let mut lookup_table: [char; 64] = ['\0'; 64];
-        for ch in 'A'..='z' {
-            let m = ch as u8 & 63;
-            lookup_table[m as usize] = ch;
-        }
-        let get_char = |m: u64| {
-            let position = m.trailing_zeros() as usize;
-            lookup_table[position]
-        };
+    let mut part2_result = Ok(0);
+    timer(|| part2_result = part2(&contents));
+    match part2_result {
+        Ok(score) => println!("Part 2 score is {score}"),
+        Err(e) => println!("Part 2 failed: {e}"),
+    }
+}
 
-        let mut score: u32 = 0;
-        let lines: Vec<&str> = contents.split('\n').collect();
-        for idx in (0..lines.len()).step_by(3) {
-            if lines[idx].is_empty() {
-                break;
-            }
-            let chunk = [lines[idx], lines[idx + 1], lines[idx + 2]];
-            let masks = chunk
-                .iter()
-                .map(|s| get_mask_for_slice(s.as_bytes().iter()));
-            let mut mask: u64 = u64::MAX;
-            for m in masks {
-                mask &= m;
-            }
-            let ch = get_char(mask);
-            let pri = get_priority(ch as u8) as u32;
-            score += pri;
-        }
-        part2_result = score;
-    };
-    timer(part2);
-    println!("Part 2 score is {part2_result}");
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod test03 {
+    use super::*;
+
+    const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+";
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_running_parts_THEN_expected_answers_returned() {
+        assert_eq!(Ok(157), part1(EXAMPLE));
+        assert_eq!(Ok(70), part2(EXAMPLE));
+    }
+
+    #[test]
+    fn GIVEN_bad_rucksacks_WHEN_running_parts_THEN_errors_name_the_line() {
+        assert_eq!(
+            Err(RucksackError::NoDuplicate { line: 2 }),
+            part1("abca\nabcd\n")
+        );
+        assert_eq!(Err(RucksackError::OddLength { line: 1 }), part1("abc\n"));
+        assert_eq!(
+            Err(RucksackError::NotAnItem { line: 1, ch: '!' }),
+            part1("a!b!\n")
+        );
+        assert_eq!(
+            Err(RucksackError::IncompleteGroup { line: 4 }),
+            part2("ab\nbc\nbd\nab\n")
+        );
+        assert_eq!(
+            Err(RucksackError::NoBadge { line: 1 }),
+            part2("ab\nbc\ncd\n")
+        );
+    }
 }
@@ -1,14 +1,10 @@
 use aoc2022::timer;
+use aoc_common::interval::IntervalSet;
 use arrayvec::ArrayVec;
 use std::fs;
 
-struct Range {
-    low: u32,
-    high: u32,
-}
-
-/// Split the line into two sets of ranges.
-fn parse_line(line: &str, linenumber: usize) -> [Range; 2] {
+/// Split the line into two sets of sections.
+fn parse_line(line: &str, linenumber: usize) -> [IntervalSet<u32>; 2] {
     line.split(',')
         .map(|range_pair| {
             let [low, high] = range_pair
@@ -20,7 +16,7 @@ fn parse_line(line: &str, linenumber: usize) -> [Range; 2] {
                 .collect::<ArrayVec<u32, 2>>()
                 .into_inner()
                 .unwrap_or_else(|_| panic!("not a range-pair at line {linenumber}"));
-            Range { low, high }
+            IntervalSet::from(low..high + 1)
         })
         .collect::<ArrayVec<_, 2>>()
         .into_inner()
@@ -44,12 +40,7 @@ fn main() {
                 break;
             }
             let [first, second] = parse_line(line, linenumber);
-            let contained = match first.low.cmp(&second.low) {
-                std::cmp::Ordering::Less => first.high >= second.high,
-                std::cmp::Ordering::Equal => true,
-                std::cmp::Ordering::Greater => first.high <= second.high,
-            };
-            if contained {
+            if first.is_subset(&second) || second.is_subset(&first) {
                 score += 1;
             }
         }
@@ -66,12 +57,7 @@ fn main() {
                 break;
             }
             let [first, second] = parse_line(line, linenumber);
-            let overlapping = match first.low.cmp(&second.low) {
-                std::cmp::Ordering::Less => first.high >= second.low,
-                std::cmp::Ordering::Equal => true,
-                std::cmp::Ordering::Greater => first.low <= second.high,
-            };
-            if overlapping {
+            if !first.intersection(&second).is_empty() {
                 score += 1;
             }
         }
@@ -4,36 +4,96 @@ use std::fs;
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        println!("USAGE: {} <filename>", args[0]);
+        println!("USAGE: {} <filename> [--replay [9000|9001]]", args[0]);
         return;
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
+    if args.get(2).is_some_and(|a| a == "--replay") {
+        let crane = match args.get(3).map(String::as_str) {
+            Some("9000") => Crane::CrateMover9000,
+            Some("9001") | None => Crane::CrateMover9001,
+            Some(model) => {
+                println!("Unknown crane model {model}, expected 9000 or 9001");
+                return;
+            }
+        };
+        replay(contents.as_str(), crane, |caption, stacks| {
+            println!("{caption}\n\n{}", stacks.render());
+        });
+    }
     println!("Part 1 answer is {}", part1(contents.as_str()));
     println!("Part 2 answer is {}", part2(contents.as_str()));
 }
 
 fn part1(contents: &str) -> String {
-    run(contents, true)
+    run(contents, Crane::CrateMover9000)
 }
 
 fn part2(contents: &str) -> String {
-    run(contents, false)
+    run(contents, Crane::CrateMover9001)
 }
 
-fn run(contents: &str, reverse: bool) -> String {
+fn run(contents: &str, crane: Crane) -> String {
+    let (mut stacks, instructions) = parse(contents);
+    for instruction in instructions.iter() {
+        stacks.execute(instruction, crane);
+    }
+    stacks.tops()
+}
+
+fn parse(contents: &str) -> (Stacks, Vec<Instruction>) {
     let (stack_lines, inst_lines): (Vec<_>, Vec<_>) = contents
         .lines()
         .filter(|line| !line.trim().is_empty())
         .partition(|line| !line.starts_with("move"));
-    let mut stacks = Stacks::new(&stack_lines);
-    let instructions = Instruction::parse_lines(inst_lines.into_iter());
-    for instruction in instructions.into_iter() {
-        stacks.execute(&instruction, reverse);
+    let stacks = Stacks::new(&stack_lines);
+    let instructions = Instruction::parse_lines(inst_lines.into_iter(), &stacks.numbers);
+    (stacks, instructions)
+}
+
+/// Replay the instructions, calling `on_frame` with the starting stacks and
+/// then after each crane operation. The CrateMover 9000 moves one crate at a
+/// time, so each of its instructions takes a frame per crate.
+fn replay(contents: &str, crane: Crane, mut on_frame: impl FnMut(&str, &Stacks)) -> Stacks {
+    let (mut stacks, instructions) = parse(contents);
+    on_frame("Starting stacks", &stacks);
+    for instruction in instructions.iter() {
+        let caption = instruction.caption(&stacks.numbers);
+        match crane {
+            Crane::CrateMover9000 => {
+                let single = Instruction {
+                    count: 1,
+                    ..*instruction
+                };
+                for crate_number in 1..=instruction.count {
+                    stacks.execute(&single, crane);
+                    on_frame(
+                        &format!("{caption} (crate {crate_number} of {})", instruction.count),
+                        &stacks,
+                    );
+                }
+            }
+            Crane::CrateMover9001 => {
+                stacks.execute(instruction, crane);
+                on_frame(&caption, &stacks);
+            }
+        }
     }
-    stacks.tops()
+    stacks
+}
+
+#[derive(Clone, Copy)]
+enum Crane {
+    /// Moves crates one at a time, so a group lands in reverse order.
+    CrateMover9000,
+    /// Moves a group of crates at once, keeping their order.
+    CrateMover9001,
 }
 
+/// A move between stacks, which are held by index rather than by the number
+/// on the footer line.
+#[derive(Clone, Copy)]
 struct Instruction {
     from: usize,
     to: usize,
@@ -41,70 +101,156 @@ struct Instruction {
 }
 
 impl Instruction {
-    fn parse_lines<'a>(iter: impl std::iter::Iterator<Item = &'a str>) -> Vec<Instruction> {
+    fn parse_lines<'a>(
+        iter: impl std::iter::Iterator<Item = &'a str>,
+        numbers: &[usize],
+    ) -> Vec<Instruction> {
         let matcher = Regex::new(r"move (\d+) from (\d+) to (\d+)")
             .expect("couldn't compile instruction regex");
+        let index = |number: usize| {
+            numbers
+                .iter()
+                .position(|&n| n == number)
+                .unwrap_or_else(|| panic!("no stack numbered {number}"))
+        };
         iter.enumerate()
             .map(|(idx, line)| {
                 let caps = matcher
                     .captures(line)
                     .unwrap_or_else(|| panic!("unexpected formatting for instruction {idx}"));
                 let count: usize = caps.get(1).unwrap().as_str().parse().unwrap();
-                let from: usize = caps.get(2).unwrap().as_str().parse().unwrap();
-                let to: usize = caps.get(3).unwrap().as_str().parse().unwrap();
+                let from = index(caps.get(2).unwrap().as_str().parse().unwrap());
+                let to = index(caps.get(3).unwrap().as_str().parse().unwrap());
                 Instruction { count, from, to }
             })
             .collect()
     }
+
+    fn caption(&self, numbers: &[usize]) -> String {
+        format!(
+            "move {} from {} to {}",
+            self.count, numbers[self.from], numbers[self.to]
+        )
+    }
 }
 
 struct Stacks {
-    stacks: Vec<Vec<char>>,
+    /// The numbers on the footer line, in the same order as the stacks.
+    numbers: Vec<usize>,
+    stacks: Vec<Vec<String>>,
 }
 
 impl Stacks {
+    ///
+    /// Read the drawing of the stacks, which ends with a footer line numbering
+    /// them.
+    ///
+    /// Each crate belongs to the stack whose number is centred nearest to the
+    /// crate, so labels may be longer than one character and stack numbers
+    /// longer than one digit, as long as the columns line up.
+    ///
     fn new(lines: &[&str]) -> Self {
-        let mut stacks = Vec::<Vec<char>>::new();
-        let matcher: Regex = Regex::new(r"\[[A-Z]\]").expect("Couldn't compile stack regex");
-        for &line in lines {
-            matcher.find_iter(line).for_each(|m| {
-                let mut pos = m.range().start;
-                let token = m.as_str().chars().nth(1).unwrap();
-                pos /= 4;
-                while stacks.len() < (pos + 1) {
-                    stacks.push(Vec::new());
-                }
-                stacks[pos].push(token);
-            });
+        let (footer, crate_lines) = lines.split_last().expect("no stack drawing found");
+        let number_matcher = Regex::new(r"\d+").expect("couldn't compile footer regex");
+        let (numbers, centres): (Vec<_>, Vec<_>) = number_matcher
+            .find_iter(footer)
+            .map(|m| (m.as_str().parse::<usize>().unwrap(), centre(m.range())))
+            .unzip();
+        if numbers.is_empty() {
+            panic!("no stack numbers on the footer line \"{footer}\"");
         }
-        stacks.iter_mut().for_each(|stack| stack.reverse());
-        Stacks { stacks }
+        let mut stacks = vec![Vec::<String>::new(); numbers.len()];
+        let crate_matcher = Regex::new(r"\[([^\[\]\s]+)\]").expect("couldn't compile stack regex");
+        for &line in crate_lines.iter().rev() {
+            for caps in crate_matcher.captures_iter(line) {
+                let position = centre(caps.get(0).unwrap().range());
+                let stack = (0..centres.len())
+                    .min_by_key(|&idx| centres[idx].abs_diff(position))
+                    .unwrap();
+                stacks[stack].push(caps[1].to_owned());
+            }
+        }
+        Stacks { numbers, stacks }
     }
+
+    /// The labels of the top crates. Empty stacks have no top, so are skipped.
     fn tops(&self) -> String {
         self.stacks
             .iter()
-            .map(|v| v.last().expect("unexpected empty stack"))
+            .filter_map(|v| v.last().map(String::as_str))
             .collect()
     }
-    fn execute(&mut self, instruction: &Instruction, reverse: bool) {
-        let count = instruction.count;
+
+    /// Move the top crates in one piece, splitting them off the source stack
+    /// and reversing them for a crane that lifts one crate at a time.
+    fn execute(&mut self, instruction: &Instruction, crane: Crane) {
         let from_stack = self
             .stacks
-            .get_mut(instruction.from - 1)
+            .get_mut(instruction.from)
             .expect("unable to get \"from\" stack");
-        let len = from_stack.len();
-        let items = from_stack.drain((len - count)..len);
-        let items: Vec<_> = match reverse {
-            true => items.rev().collect(),
-            false => items.collect(),
-        };
+        let split = from_stack
+            .len()
+            .checked_sub(instruction.count)
+            .expect("not enough crates to move");
+        let mut items = from_stack.split_off(split);
+        if let Crane::CrateMover9000 = crane {
+            items.reverse();
+        }
         self.stacks
-            .get_mut(instruction.to - 1)
+            .get_mut(instruction.to)
             .expect("unable to get \"to\" stack")
-            .extend(items);
+            .append(&mut items);
+    }
+
+    ///
+    /// Draw the stacks as the puzzle does, with each crate in brackets above
+    /// the footer line.
+    ///
+    /// Every column is wide enough for its longest label and its number, with
+    /// shorter labels and the number centred in it.
+    ///
+    fn render(&self) -> String {
+        let widths = self
+            .stacks
+            .iter()
+            .zip(&self.numbers)
+            .map(|(stack, number)| {
+                let label = stack.iter().map(String::len).max().unwrap_or(1);
+                (label + 2).max(number.to_string().len())
+            })
+            .collect::<Vec<_>>();
+        let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+        let mut drawing = String::new();
+        for level in (0..height).rev() {
+            let row = self
+                .stacks
+                .iter()
+                .zip(&widths)
+                .map(|(stack, &width)| match stack.get(level) {
+                    Some(label) => format!("{:^width$}", format!("[{label}]")),
+                    None => " ".repeat(width),
+                })
+                .collect::<Vec<_>>();
+            drawing.push_str(&row.join(" "));
+            drawing.push('\n');
+        }
+        let footer = self
+            .numbers
+            .iter()
+            .zip(&widths)
+            .map(|(number, &width)| format!("{number:^width$}"))
+            .collect::<Vec<_>>();
+        drawing.push_str(&footer.join(" "));
+        drawing.push('\n');
+        drawing
     }
 }
 
+/// The middle of a range of columns, doubled so that it stays whole.
+fn centre(range: std::ops::Range<usize>) -> usize {
+    range.start + range.end - 1
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod test05 {
@@ -126,9 +272,10 @@ mod test05 {
         let stacks = Stacks::new(&lines);
         assert_eq!(3, stacks.stacks.len());
 
-        assert_eq!(vec!['Z', 'N'], stacks.stacks[0]);
-        assert_eq!(vec!['M', 'C', 'D'], stacks.stacks[1]);
-        assert_eq!(vec!['P'], stacks.stacks[2]);
+        assert_eq!(vec!["Z", "N"], stacks.stacks[0]);
+        assert_eq!(vec!["M", "C", "D"], stacks.stacks[1]);
+        assert_eq!(vec!["P"], stacks.stacks[2]);
+        assert_eq!(vec![1, 2, 3], stacks.numbers);
     }
 
     static AOC_EXAMPLE_INPUT: &str = r#"
@@ -154,4 +301,115 @@ move 1 from 1 to 2
         let tops = part2(AOC_EXAMPLE_INPUT);
         assert_eq!("MCD".to_owned(), tops);
     }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_replaying_9000_THEN_frames_match_walkthrough() {
+        let mut frames = Vec::new();
+        let stacks = replay(
+            AOC_EXAMPLE_INPUT,
+            Crane::CrateMover9000,
+            |caption, stacks| frames.push(format!("{caption}\n{}", stacks.render())),
+        );
+        // a frame for the start and one for each of the 7 crates moved
+        assert_eq!(8, frames.len());
+        assert_eq!(
+            "Starting stacks
+    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+",
+            frames[0]
+        );
+        // the frames that finish each instruction
+        let last_frames = [1, 4, 6, 7].map(|idx| frames[idx].as_str());
+        assert_eq!(
+            vec![
+                "move 1 from 2 to 1 (crate 1 of 1)
+[D]        
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+",
+                "move 3 from 1 to 3 (crate 3 of 3)
+        [Z]
+        [N]
+    [C] [D]
+    [M] [P]
+ 1   2   3 
+",
+                "move 2 from 2 to 1 (crate 2 of 2)
+        [Z]
+        [N]
+[M]     [D]
+[C]     [P]
+ 1   2   3 
+",
+                "move 1 from 1 to 2 (crate 1 of 1)
+        [Z]
+        [N]
+        [D]
+[C] [M] [P]
+ 1   2   3 
+",
+            ],
+            last_frames
+        );
+        assert_eq!("CMZ", stacks.tops());
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_replaying_9001_THEN_crates_keep_their_order() {
+        let mut frames = Vec::new();
+        replay(
+            AOC_EXAMPLE_INPUT,
+            Crane::CrateMover9001,
+            |caption, stacks| frames.push(format!("{caption}\n{}", stacks.render())),
+        );
+        assert_eq!(5, frames.len());
+        assert_eq!(
+            "move 3 from 1 to 3
+        [D]
+        [N]
+    [C] [Z]
+    [M] [P]
+ 1   2   3 
+",
+            frames[2]
+        );
+    }
+
+    #[test]
+    fn GIVEN_long_labels_and_many_stacks_WHEN_parsing_THEN_columns_follow_footer() {
+        let text = "
+                                                 [JK]
+[AB] [CD]                                        [IJ]
+[EF] [GH] [  ]                                   [KL]
+ 1    2    3    4    5    6    7    8    9    10   11 
+move 2 from 11 to 3
+move 1 from 1 to 10
+";
+        // the blank label isn't a crate, so stack 3 starts empty
+        let (stacks, _) = parse(text);
+        assert_eq!((1..=11).collect::<Vec<_>>(), stacks.numbers);
+        assert_eq!(vec!["EF", "AB"], stacks.stacks[0]);
+        assert_eq!(vec!["GH", "CD"], stacks.stacks[1]);
+        assert!(stacks.stacks[2..10].iter().all(Vec::is_empty));
+        assert_eq!(vec!["KL", "IJ", "JK"], stacks.stacks[10]);
+
+        let stacks = replay(text, Crane::CrateMover9001, |_, _| {});
+        assert_eq!(vec!["IJ", "JK"], stacks.stacks[2]);
+        assert_eq!(vec!["AB"], stacks.stacks[9]);
+        assert_eq!("EFCDJKABKL", stacks.tops());
+        assert_eq!(
+            "     [CD] [JK]                                  
+[EF] [GH] [IJ]                         [AB] [KL]
+ 1    2    3    4   5   6   7   8   9   10   11 
+",
+            stacks.render()
+        );
+        // the drawing reads back as the same stacks
+        let redrawn = Stacks::new(&stacks.render().lines().collect::<Vec<_>>());
+        assert_eq!(stacks.stacks, redrawn.stacks);
+    }
 }
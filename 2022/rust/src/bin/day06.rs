@@ -1,73 +1,327 @@
-use std::fs;
+use aoc2022::timer;
+use aoc_common::byte_set::ByteSet;
+use std::{fmt, fs};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        println!("USAGE: {} <filename>", args[0]);
+        println!("USAGE: {} <filename> [--bench]", args[0]);
         return;
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
-    println!("Part 1 answer is {}", part1(contents.as_str()));
-    println!("Part 2 answer is {}", part2(contents.as_str()));
+    let contents = contents.trim_end();
+    for (name, result) in [("Part 1", part1(contents)), ("Part 2", part2(contents))] {
+        match result {
+            Ok(answer) => println!("{name} answer is {answer}"),
+            Err(e) => println!("{name} failed: {e}"),
+        }
+    }
+
+    if args.get(2).is_some_and(|a| a == "--bench") {
+        for (name, finder) in finders() {
+            for window in [4, 14] {
+                println!("{name} finder, window of {window}:");
+                timer(|| {
+                    std::hint::black_box(finder(contents.as_bytes(), window));
+                });
+            }
+        }
+    }
 }
 
-fn part1(contents: &str) -> usize {
+fn part1(contents: &str) -> Result<usize, MarkerError> {
     find_distinct_set(contents, 4)
 }
 
-fn part2(contents: &str) -> usize {
+fn part2(contents: &str) -> Result<usize, MarkerError> {
     find_distinct_set(contents, 14)
 }
 
-fn find_distinct_set(contents: &str, test_length: usize) -> usize {
-    let buffer = contents.as_bytes();
+#[derive(Debug, Eq, PartialEq)]
+enum MarkerError {
+    EmptyWindow,
+    /// There are only [`MAX_WINDOW`] distinct bytes.
+    WindowTooLong(usize),
+    NotFound(usize),
+}
 
-    let tester = |buf_idx: usize| -> bool {
-        let mut bit_table: u64 = 0;
-        for idx in 0..test_length {
-            let ch = buffer[buf_idx + idx];
-            let mask = get_mask(ch);
-            if (mask & bit_table) > 0 {
-                return false;
+impl fmt::Display for MarkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyWindow => write!(f, "the window length must be at least 1"),
+            Self::WindowTooLong(len) => {
+                write!(
+                    f,
+                    "a window of {len} can't hold that many distinct characters"
+                )
             }
-            bit_table |= mask;
+            Self::NotFound(len) => write!(f, "no {len} consecutive characters are all different"),
         }
-        true
-    };
-    for buf_idx in 0..buffer.len() - test_length {
-        if tester(buf_idx) {
-            return buf_idx + test_length;
+    }
+}
+
+/// The longest window that can hold distinct bytes.
+const MAX_WINDOW: usize = 256;
+
+/// Returns the number of characters read up to the end of the first window of
+/// `test_length` distinct characters, using the widest finder the CPU supports.
+fn find_distinct_set(contents: &str, test_length: usize) -> Result<usize, MarkerError> {
+    if test_length == 0 {
+        return Err(MarkerError::EmptyWindow);
+    }
+    if test_length > MAX_WINDOW {
+        return Err(MarkerError::WindowTooLong(test_length));
+    }
+    let (_name, finder) = finders()
+        .pop()
+        .expect("the scalar finder is always available");
+    finder(contents.as_bytes(), test_length).ok_or(MarkerError::NotFound(test_length))
+}
+
+type Finder = fn(&[u8], usize) -> Option<usize>;
+
+/// The finders that this CPU can run, from scalar to the widest SIMD lanes.
+fn finders() -> Vec<(&'static str, Finder)> {
+    #[allow(unused_mut)]
+    let mut finders: Vec<(&'static str, Finder)> = vec![("scalar", find_marker_scalar)];
+    #[cfg(target_arch = "x86_64")]
+    {
+        finders.push(("SSE2", simd::find_marker_sse2));
+        if is_x86_feature_detected!("avx2") {
+            finders.push(("AVX2", simd::find_marker_avx2));
         }
     }
-    panic!("not found");
+    finders
 }
 
-fn get_mask(ch: u8) -> u64 {
-    let position = ch % 64;
-    1 << position
+///
+/// Slide a window along the buffer, keeping the characters in it as a set and
+/// the window free of repeats.
+///
+/// Characters enter and leave the set by toggling their bit, so each is
+/// touched at most twice. When the incoming character is already in the
+/// window, the window jumps ahead past its earlier copy, as no window holding
+/// both can be a marker. Without repeats, the set's population count is just
+/// the window's length, so the window is a marker once that reaches
+/// `test_length`.
+///
+fn find_marker_scalar(buffer: &[u8], test_length: usize) -> Option<usize> {
+    let mut seen = ByteSet::new();
+    let mut start = 0;
+    for (end, &ch) in buffer.iter().enumerate() {
+        if seen.contains(ch) {
+            while buffer[start] != ch {
+                seen.toggle(buffer[start]);
+                start += 1;
+            }
+            // the earlier copy leaves as this one enters, so its bit stays set
+            start += 1;
+        } else {
+            seen.toggle(ch);
+        }
+        debug_assert_eq!(seen.len(), end + 1 - start);
+        if end + 1 - start == test_length {
+            return Some(end + 1);
+        }
+    }
+    None
+}
+
+///
+/// Check a block of consecutive windows at once, one window per byte lane.
+///
+/// Comparing the bytes loaded from offsets `a` and `b` past the block start
+/// flags, in lane `i`, a repeat within the window starting at `i`. ORing the
+/// comparisons for every pair of offsets in a window leaves a zero lane for
+/// each window that is a marker. That is O(k²) comparisons per block, but
+/// each covers 16 or 32 windows.
+///
+/// Like the scalar finder, these find nothing for an empty window or one
+/// longer than [`MAX_WINDOW`](super::MAX_WINDOW).
+///
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use std::arch::x86_64::*;
+
+    use super::{find_marker_scalar, MAX_WINDOW};
+
+    pub fn find_marker_sse2(buffer: &[u8], test_length: usize) -> Option<usize> {
+        const LANES: usize = 16;
+        if !(1..=MAX_WINDOW).contains(&test_length) {
+            return None;
+        }
+        let mut start = 0;
+        // SAFETY: SSE2 is part of the x86-64 baseline.
+        let mut windows = [unsafe { _mm_setzero_si128() }; MAX_WINDOW];
+        while start + test_length - 1 + LANES <= buffer.len() {
+            // SAFETY: SSE2 is part of the x86-64 baseline, and the loop
+            // condition keeps every LANES-byte load, at an offset below
+            // test_length, inside the buffer.
+            let repeats = unsafe {
+                for (offset, window) in windows.iter_mut().take(test_length).enumerate() {
+                    let ptr = buffer.as_ptr().add(start + offset) as *const __m128i;
+                    *window = _mm_loadu_si128(ptr);
+                }
+                let mut repeats = _mm_setzero_si128();
+                for a in 0..test_length {
+                    for b in a + 1..test_length {
+                        let same = _mm_cmpeq_epi8(windows[a], windows[b]);
+                        repeats = _mm_or_si128(repeats, same);
+                    }
+                }
+                _mm_movemask_epi8(repeats) as u32 | !0xffff
+            };
+            if repeats != u32::MAX {
+                return Some(start + (!repeats).trailing_zeros() as usize + test_length);
+            }
+            start += LANES;
+        }
+        find_marker_scalar(&buffer[start..], test_length).map(|end| start + end)
+    }
+
+    pub fn find_marker_avx2(buffer: &[u8], test_length: usize) -> Option<usize> {
+        assert!(is_x86_feature_detected!("avx2"));
+        // SAFETY: AVX2 support was checked above.
+        unsafe { find_marker_avx2_unchecked(buffer, test_length) }
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn find_marker_avx2_unchecked(buffer: &[u8], test_length: usize) -> Option<usize> {
+        const LANES: usize = 32;
+        if !(1..=MAX_WINDOW).contains(&test_length) {
+            return None;
+        }
+        let mut start = 0;
+        let mut windows = [_mm256_setzero_si256(); MAX_WINDOW];
+        while start + test_length - 1 + LANES <= buffer.len() {
+            for (offset, window) in windows.iter_mut().take(test_length).enumerate() {
+                // SAFETY: the loop condition keeps every LANES-byte load, at
+                // an offset below test_length, inside the buffer.
+                *window = unsafe {
+                    let ptr = buffer.as_ptr().add(start + offset) as *const __m256i;
+                    _mm256_loadu_si256(ptr)
+                };
+            }
+            let mut repeats = _mm256_setzero_si256();
+            for a in 0..test_length {
+                for b in a + 1..test_length {
+                    let same = _mm256_cmpeq_epi8(windows[a], windows[b]);
+                    repeats = _mm256_or_si256(repeats, same);
+                }
+            }
+            let repeats = _mm256_movemask_epi8(repeats) as u32;
+            if repeats != u32::MAX {
+                return Some(start + (!repeats).trailing_zeros() as usize + test_length);
+            }
+            start += LANES;
+        }
+        find_marker_scalar(&buffer[start..], test_length).map(|end| start + end)
+    }
 }
 
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod test06 {
     use super::*;
+    use aoc_common::test_util::XorShift;
 
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_1_THEN_expected_answers_returned() {
-        assert_eq!(7, part1("mjqjpqmgbljsphdztnvjfqwrcgsmlb"));
-        assert_eq!(5, part1("bvwbjplbgvbhsrlpgdmjqwftvncz"));
-        assert_eq!(6, part1("nppdvjthqldpwncqszvftbrmjlhg"));
-        assert_eq!(10, part1("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"));
-        assert_eq!(11, part1("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"));
+        assert_eq!(Ok(7), part1("mjqjpqmgbljsphdztnvjfqwrcgsmlb"));
+        assert_eq!(Ok(5), part1("bvwbjplbgvbhsrlpgdmjqwftvncz"));
+        assert_eq!(Ok(6), part1("nppdvjthqldpwncqszvftbrmjlhg"));
+        assert_eq!(Ok(10), part1("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"));
+        assert_eq!(Ok(11), part1("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"));
     }
 
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_2_THEN_expected_answers_returned() {
-        assert_eq!(19, part2("mjqjpqmgbljsphdztnvjfqwrcgsmlb"));
-        assert_eq!(23, part2("bvwbjplbgvbhsrlpgdmjqwftvncz"));
-        assert_eq!(23, part2("nppdvjthqldpwncqszvftbrmjlhg"));
-        assert_eq!(29, part2("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"));
-        assert_eq!(26, part2("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"));
+        assert_eq!(Ok(19), part2("mjqjpqmgbljsphdztnvjfqwrcgsmlb"));
+        assert_eq!(Ok(23), part2("bvwbjplbgvbhsrlpgdmjqwftvncz"));
+        assert_eq!(Ok(23), part2("nppdvjthqldpwncqszvftbrmjlhg"));
+        assert_eq!(Ok(29), part2("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"));
+        assert_eq!(Ok(26), part2("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"));
+    }
+
+    #[test]
+    fn GIVEN_unusable_windows_WHEN_searching_THEN_errors_returned() {
+        assert_eq!(Err(MarkerError::EmptyWindow), find_distinct_set("abc", 0));
+        assert_eq!(
+            Err(MarkerError::WindowTooLong(257)),
+            find_distinct_set("abc", 257)
+        );
+        assert_eq!(Err(MarkerError::NotFound(4)), find_distinct_set("abc", 4));
+        assert_eq!(
+            Err(MarkerError::NotFound(3)),
+            find_distinct_set("abbaabbaab", 3)
+        );
+        // the marker may end on the very last character
+        assert_eq!(Ok(3), find_distinct_set("abc", 3));
+        assert_eq!(Ok(12), find_distinct_set("aaaaaaaaabcd", 4));
+    }
+
+    fn brute_force(buffer: &[u8], test_length: usize) -> Option<usize> {
+        buffer
+            .windows(test_length)
+            .position(|w| {
+                let mut seen = [false; 256];
+                w.iter()
+                    .all(|&ch| !std::mem::replace(&mut seen[ch as usize], true))
+            })
+            .map(|start| start + test_length)
+    }
+
+    #[test]
+    fn GIVEN_random_buffers_WHEN_searching_any_window_THEN_all_finders_match_brute_force() {
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        for _ in 0..2000 {
+            let len = rng.below(300) as usize;
+            // smaller alphabets make markers rarer and push them later into
+            // the buffer
+            let alphabet = 1 + rng.below(256);
+            let first = rng.below(256) as u8;
+            let buffer = (0..len)
+                .map(|_| first.wrapping_add(rng.below(alphabet) as u8))
+                .collect::<Vec<_>>();
+            let test_length = 1 + rng.below(alphabet.min(80) + 2) as usize;
+            let expected = brute_force(&buffer, test_length);
+            for (name, finder) in finders() {
+                assert_eq!(
+                    expected,
+                    finder(&buffer, test_length),
+                    "{name} finder, window {test_length}, buffer {:?}",
+                    String::from_utf8_lossy(&buffer)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn GIVEN_characters_equal_mod_64_WHEN_searching_THEN_all_finders_tell_them_apart() {
+        for (name, finder) in finders() {
+            assert_eq!(Some(4), finder(b"a!bcd", 4), "{name} finder");
+            let mut long = vec![b'!'; 100];
+            long.extend(b"abcd");
+            assert_eq!(Some(103), finder(&long, 4), "{name} finder");
+        }
+    }
+
+    #[test]
+    fn GIVEN_every_byte_value_WHEN_searching_widest_window_THEN_marker_found() {
+        let mut buffer = vec![0u8; 40];
+        buffer.extend(0..=255);
+        for (name, finder) in finders() {
+            assert_eq!(Some(296), finder(&buffer, 256), "{name} finder");
+        }
+    }
+
+    #[test]
+    fn GIVEN_unusable_windows_WHEN_calling_finders_directly_THEN_nothing_found() {
+        let buffer = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+        for (name, finder) in finders() {
+            assert_eq!(None, finder(&buffer, 0), "{name} finder");
+            assert_eq!(None, finder(&buffer, 257), "{name} finder");
+        }
     }
 }
@@ -1,149 +1,398 @@
-use std::cell::RefCell;
-use std::{fs, rc::Rc};
+use std::{fmt, fs};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        println!("USAGE: {} <filename>", args[0]);
+        println!("USAGE: {} <filename> [--tree [<path>]]", args[0]);
         return;
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
-    println!("Part 1 answer is {}", part1(contents.as_str()));
-    println!("Part 2 answer is {}", part2(contents.as_str()));
+    let filesystem = match FileSystem::from_transcript(contents.as_str()) {
+        Ok(filesystem) => filesystem,
+        Err(e) => {
+            println!("Unable to read the transcript: {e}");
+            return;
+        }
+    };
+    if args.get(2).is_some_and(|a| a == "--tree") {
+        let path = args.get(3).map_or("/", String::as_str);
+        match filesystem.resolve(ROOT, path) {
+            Some(node) => print!("{}", filesystem.tree(node)),
+            None => println!("{path} isn't in the transcript"),
+        }
+    }
+    println!("Part 1 answer is {}", part1(&filesystem));
+    match part2(&filesystem) {
+        Ok(answer) => println!("Part 2 answer is {answer}"),
+        Err(e) => println!("Part 2 failed: {e}"),
+    }
+}
+
+/// The size of the disk the transcript was taken from.
+const TOTAL_BYTES: usize = 70000000;
+
+/// The total size of the directories of at most 100000 bytes.
+fn part1(filesystem: &FileSystem) -> usize {
+    const THRESHOLD: usize = 100000;
+    filesystem
+        .find_dirs(|size| size <= THRESHOLD)
+        .into_iter()
+        .map(|(_, size)| size)
+        .sum()
 }
 
-fn part1(contents: &str) -> usize {
-    static THRESHOLD: usize = 100000;
+/// The size of the smallest directory that frees enough space when deleted.
+fn part2(filesystem: &FileSystem) -> Result<usize, TranscriptError> {
+    const TARGET_BYTES: usize = 30000000;
+    let used = filesystem.du()[ROOT];
+    let free = TOTAL_BYTES
+        .checked_sub(used)
+        .ok_or(TranscriptError::DiskOverflow { used })?;
+    let required_space = TARGET_BYTES.saturating_sub(free);
+    // the root directory always frees enough
+    Ok(filesystem
+        .find_dirs(|size| size >= required_space)
+        .into_iter()
+        .map(|(_, size)| size)
+        .min()
+        .unwrap())
+}
 
-    let root = parse_file(contents);
+#[derive(Debug, Eq, PartialEq)]
+enum TranscriptError {
+    UnknownCommand {
+        line: usize,
+        command: String,
+    },
+    BadEntry {
+        line: usize,
+    },
+    NotADirectory {
+        line: usize,
+        path: String,
+    },
+    AboveRoot {
+        line: usize,
+    },
+    /// A directory listed twice, with an entry that disagrees between the
+    /// two listings.
+    ListingChanged {
+        line: usize,
+        path: String,
+    },
+    /// The files add up to more than the disk holds.
+    DiskOverflow {
+        used: usize,
+    },
+}
 
-    let mut answer: usize = 0;
-    fn walk(node: &DirNode, answer: &mut usize) -> usize {
-        let mut total: usize = 0;
-        for subdir in node.borrow().subdirs.iter() {
-            total += walk(subdir, answer);
-        }
-        for file in node.borrow().files.iter() {
-            total += file.1;
-        }
-        if total <= THRESHOLD {
-            *answer += total;
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCommand { line, command } => {
+                write!(f, "unrecognized command \"{command}\" on line {line}")
+            }
+            Self::BadEntry { line } => write!(f, "can't read the listing entry on line {line}"),
+            Self::NotADirectory { line, path } => {
+                write!(f, "{path} is not a directory on line {line}")
+            }
+            Self::AboveRoot { line } => write!(f, "cd above the root on line {line}"),
+            Self::ListingChanged { line, path } => {
+                write!(f, "{path} was listed differently before, on line {line}")
+            }
+            Self::DiskOverflow { used } => {
+                write!(
+                    f,
+                    "the files take {used} bytes, more than the disk's {TOTAL_BYTES}"
+                )
+            }
         }
-        total
     }
-    walk(&root, &mut answer);
-    answer
 }
 
-fn part2(contents: &str) -> usize {
-    static TOTAL_BYTES: usize = 70000000;
-    static TARGET_BYTES: usize = 30000000;
-    let root = parse_file(contents);
-    fn walk(node: &DirNode, sizes: &mut Vec<usize>) -> usize {
-        let mut total: usize = 0;
-        for subdir in node.borrow().subdirs.iter() {
-            total += walk(subdir, sizes);
-        }
-        for file in node.borrow().files.iter() {
-            total += file.1;
-        }
-        sizes.push(total);
-        total
-    }
-    let mut sizes: Vec<usize> = Vec::new();
-    let root_size = walk(&root, &mut sizes);
-    let required_space = TARGET_BYTES - (TOTAL_BYTES - root_size);
-    let candidates = sizes
-        .into_iter()
-        .filter(|n| *n > required_space)
-        .collect::<Vec<_>>();
-    *candidates.iter().min().unwrap()
-}
+type NodeId = usize;
 
-type DirNode = Rc<RefCell<Directory>>;
+/// The root directory is always the first node.
+const ROOT: NodeId = 0;
 
-struct Directory {
-    #[allow(dead_code)]
+#[derive(Debug, PartialEq)]
+enum NodeKind {
+    /// The children are kept in the order they were first seen.
+    Dir {
+        children: Vec<NodeId>,
+        listed: bool,
+    },
+    File {
+        size: usize,
+    },
+}
+
+#[derive(Debug)]
+struct Node {
     name: String,
-    files: Vec<(String, usize)>,
-    subdirs: Vec<DirNode>,
+    parent: Option<NodeId>,
+    kind: NodeKind,
+}
+
+///
+/// A directory tree held in a single arena, with nodes referring to their
+/// parent and children by index.
+///
+/// Nodes are only ever added, and always after their parent, so a pass over
+/// the arena from the back sees every node before its parent.
+///
+struct FileSystem {
+    nodes: Vec<Node>,
 }
 
-impl Directory {
-    fn new(name: &str) -> DirNode {
-        Rc::new(RefCell::new(Self {
+impl FileSystem {
+    fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: "/".to_owned(),
+                parent: None,
+                kind: NodeKind::Dir {
+                    children: Vec::new(),
+                    listed: false,
+                },
+            }],
+        }
+    }
+
+    fn from_transcript(contents: &str) -> Result<Self, TranscriptError> {
+        let mut shell = Shell::new();
+        for (idx, line) in contents.lines().enumerate() {
+            if !line.trim().is_empty() {
+                shell.parse_line(line, idx + 1)?;
+            }
+        }
+        Ok(shell.filesystem)
+    }
+
+    fn children(&self, dir: NodeId) -> &[NodeId] {
+        match &self.nodes[dir].kind {
+            NodeKind::Dir { children, .. } => children,
+            NodeKind::File { .. } => &[],
+        }
+    }
+
+    fn is_dir(&self, node: NodeId) -> bool {
+        matches!(self.nodes[node].kind, NodeKind::Dir { .. })
+    }
+
+    fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        self.children(dir)
+            .iter()
+            .copied()
+            .find(|&child| self.nodes[child].name == name)
+    }
+
+    fn add_child(&mut self, dir: NodeId, name: &str, kind: NodeKind) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
             name: name.to_owned(),
-            files: Vec::new(),
-            subdirs: Vec::new(),
-        }))
+            parent: Some(dir),
+            kind,
+        });
+        match &mut self.nodes[dir].kind {
+            NodeKind::Dir { children, .. } => children.push(id),
+            NodeKind::File { .. } => panic!("can't add {name} to a file"),
+        }
+        id
     }
-}
 
-struct Reader {
-    dir_stack: Vec<DirNode>,
-}
+    /// Follow a path such as `/a/e`, `..` or `d/../a` from a directory.
+    fn resolve(&self, from: NodeId, path: &str) -> Option<NodeId> {
+        let start = match path.starts_with('/') {
+            true => ROOT,
+            false => from,
+        };
+        path.split('/')
+            .filter(|step| !step.is_empty() && *step != ".")
+            .try_fold(start, |dir, step| match step {
+                ".." => self.nodes[dir].parent,
+                name => self.child(dir, name),
+            })
+    }
 
-impl Reader {
-    fn get_root(self) -> DirNode {
-        self.dir_stack[0].clone()
+    fn path(&self, node: NodeId) -> String {
+        match self.nodes[node].parent {
+            None => "/".to_owned(),
+            Some(ROOT) => format!("/{}", self.nodes[node].name),
+            Some(parent) => format!("{}/{}", self.path(parent), self.nodes[node].name),
+        }
     }
-    fn parse_line(&mut self, line: &str) {
-        let toks = line
-            .split(' ')
-            .filter(|&s| !s.is_empty())
+
+    /// The total size of every node, indexed by node.
+    fn du(&self) -> Vec<usize> {
+        let mut sizes = self
+            .nodes
+            .iter()
+            .map(|node| match node.kind {
+                NodeKind::File { size } => size,
+                NodeKind::Dir { .. } => 0,
+            })
             .collect::<Vec<_>>();
-        match toks[0] {
-            "$" => self.parse_command(&toks[1..]),
-            "dir" => (),
-            size_str => {
-                let filename = toks[1];
-                let size = size_str.parse::<usize>();
-                let working_dir = self.dir_stack.last().expect("working directory unset");
-                let mut working_dir = working_dir.borrow_mut();
-                working_dir.files.push((
-                    filename.to_string(),
-                    size.expect("unable to parse file size"),
-                ));
+        for node in (1..self.nodes.len()).rev() {
+            let parent = self.nodes[node].parent.unwrap();
+            sizes[parent] += sizes[node];
+        }
+        sizes
+    }
+
+    /// The directories whose total size passes the test, like `find -size`,
+    /// with their sizes.
+    fn find_dirs(&self, size_test: impl Fn(usize) -> bool) -> Vec<(NodeId, usize)> {
+        self.du()
+            .into_iter()
+            .enumerate()
+            .filter(|&(node, size)| self.is_dir(node) && size_test(size))
+            .collect()
+    }
+
+    /// The indented listing of a directory and everything under it, as the
+    /// puzzle draws it.
+    fn tree(&self, dir: NodeId) -> String {
+        let mut listing = String::new();
+        self.tree_into(dir, 0, &mut listing);
+        listing
+    }
+
+    fn tree_into(&self, node: NodeId, depth: usize, listing: &mut String) {
+        let indent = "  ".repeat(depth);
+        let name = &self.nodes[node].name;
+        match self.nodes[node].kind {
+            NodeKind::Dir { .. } => listing.push_str(&format!("{indent}- {name} (dir)\n")),
+            NodeKind::File { size } => {
+                listing.push_str(&format!("{indent}- {name} (file, size={size})\n"))
             }
         }
+        for &child in self.children(node) {
+            self.tree_into(child, depth + 1, listing);
+        }
+    }
+}
+
+/// Replays a terminal transcript, building the filesystem it explores.
+struct Shell {
+    filesystem: FileSystem,
+    cwd: NodeId,
+    /// Whether the `ls` being read is of a directory listed before.
+    relisting: bool,
+}
+
+impl Shell {
+    fn new() -> Self {
+        Self {
+            filesystem: FileSystem::new(),
+            cwd: ROOT,
+            relisting: false,
+        }
     }
 
-    fn parse_command(&mut self, toks: &[&str]) {
-        match toks[0] {
-            "cd" => match toks[1] {
-                "/" => {
-                    let rootdir = Directory::new("<root>");
-                    self.dir_stack.push(rootdir);
-                }
-                ".." => {
-                    self.dir_stack.pop();
-                }
-                subdir_name => {
-                    let subdir = Directory::new(subdir_name);
-                    self.dir_stack.push(subdir.clone());
-                    let len = self.dir_stack.len();
-                    let working_dir = &self.dir_stack[len - 2];
-                    let mut working_dir = working_dir.borrow_mut();
-                    working_dir.subdirs.push(subdir);
+    fn parse_line(&mut self, line: &str, line_number: usize) -> Result<(), TranscriptError> {
+        let toks = line.split_whitespace().collect::<Vec<_>>();
+        match toks[..] {
+            ["$", ref command @ ..] => self.parse_command(command, line_number),
+            [first, name] => self.parse_entry(first, name, line_number),
+            _ => Err(TranscriptError::BadEntry { line: line_number }),
+        }
+    }
+
+    fn parse_command(&mut self, toks: &[&str], line_number: usize) -> Result<(), TranscriptError> {
+        match toks {
+            ["cd", path] => self.cd(path, line_number),
+            ["ls"] => {
+                if let NodeKind::Dir { listed, .. } = &mut self.filesystem.nodes[self.cwd].kind {
+                    self.relisting = std::mem::replace(listed, true);
                 }
-            },
-            "ls" => (),
-            other_cmd => {
-                panic!("unrecognized command: \"{other_cmd}\"");
+                Ok(())
             }
+            _ => Err(TranscriptError::UnknownCommand {
+                line: line_number,
+                command: toks.join(" "),
+            }),
         }
     }
-}
 
-fn parse_file(contents: &str) -> Rc<RefCell<Directory>> {
-    let mut parser = Reader {
-        dir_stack: Vec::new(),
-    };
-    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
-        parser.parse_line(line);
+    /// Change directory one step at a time, returning to any directory seen
+    /// before and creating those that haven't been listed yet.
+    fn cd(&mut self, path: &str, line_number: usize) -> Result<(), TranscriptError> {
+        if path.starts_with('/') {
+            self.cwd = ROOT;
+        }
+        for step in path.split('/').filter(|s| !s.is_empty() && *s != ".") {
+            let fs = &mut self.filesystem;
+            self.cwd = match step {
+                ".." => fs.nodes[self.cwd]
+                    .parent
+                    .ok_or(TranscriptError::AboveRoot { line: line_number })?,
+                name => match fs.child(self.cwd, name) {
+                    Some(child) if fs.is_dir(child) => child,
+                    Some(child) => {
+                        return Err(TranscriptError::NotADirectory {
+                            line: line_number,
+                            path: fs.path(child),
+                        })
+                    }
+                    None => fs.add_child(self.cwd, name, Self::unlisted_dir()),
+                },
+            };
+        }
+        Ok(())
+    }
+
+    fn unlisted_dir() -> NodeKind {
+        NodeKind::Dir {
+            children: Vec::new(),
+            listed: false,
+        }
+    }
+
+    /// Record a line of `ls` output. Entries seen before must agree, so that a
+    /// directory listed twice isn't counted twice.
+    fn parse_entry(
+        &mut self,
+        first: &str,
+        name: &str,
+        line_number: usize,
+    ) -> Result<(), TranscriptError> {
+        let kind = match first {
+            "dir" => Self::unlisted_dir(),
+            size => NodeKind::File {
+                size: size
+                    .parse::<usize>()
+                    .map_err(|_| TranscriptError::BadEntry { line: line_number })?,
+            },
+        };
+        let fs = &mut self.filesystem;
+        match fs.child(self.cwd, name) {
+            Some(existing) => {
+                let agrees = match (&fs.nodes[existing].kind, &kind) {
+                    (NodeKind::Dir { .. }, NodeKind::Dir { .. }) => true,
+                    (existing, kind) => existing == kind,
+                };
+                if !agrees {
+                    return Err(TranscriptError::ListingChanged {
+                        line: line_number,
+                        path: fs.path(existing),
+                    });
+                }
+            }
+            None if self.relisting => {
+                let added = fs.add_child(self.cwd, name, kind);
+                return Err(TranscriptError::ListingChanged {
+                    line: line_number,
+                    path: fs.path(added),
+                });
+            }
+            None => {
+                fs.add_child(self.cwd, name, kind);
+            }
+        }
+        Ok(())
     }
-    parser.get_root()
 }
 
 #[cfg(test)]
@@ -153,35 +402,32 @@ mod tester {
 
     #[test]
     fn GIVEN_initial_commands_WHEN_reader_parsing_THEN_state_changes_as_expected() {
-        let mut parser = Reader {
-            dir_stack: Vec::new(),
-        };
-        assert_eq!(0, parser.dir_stack.len());
-        parser.parse_line("$ cd /");
-        assert_eq!(1, parser.dir_stack.len());
+        let mut shell = Shell::new();
+        assert_eq!(1, shell.filesystem.nodes.len());
+        shell.parse_line("$ cd /", 1).unwrap();
+        assert_eq!(ROOT, shell.cwd);
+        assert_eq!("/", shell.filesystem.nodes[ROOT].name);
+        assert!(shell.filesystem.children(ROOT).is_empty());
+        shell.parse_line("126880 fmftdzrp.fwt", 2).unwrap();
         {
-            let dir = parser.dir_stack.last().unwrap();
-            assert_eq!("<root>", dir.borrow().name);
-            assert_eq!(0, dir.borrow().files.len());
+            let file = shell.filesystem.child(ROOT, "fmftdzrp.fwt").unwrap();
+            assert_eq!(1, shell.filesystem.children(ROOT).len());
+            assert_eq!(
+                NodeKind::File { size: 126880 },
+                shell.filesystem.nodes[file].kind
+            );
         }
-        parser.parse_line("126880 fmftdzrp.fwt");
-        {
-            let dir = parser.dir_stack.last().unwrap();
-            assert_eq!(1, dir.borrow().files.len()); //
-            assert_eq!(126880, dir.borrow().files[0].1);
-            assert_eq!("fmftdzrp.fwt", dir.borrow().files[0].0);
-        }
-        parser.parse_line("$ cd a");
-        parser.parse_line("29116 f");
-        assert_eq!(2, parser.dir_stack.len());
+        shell.parse_line("$ cd a", 3).unwrap();
+        shell.parse_line("29116 f", 4).unwrap();
         {
-            let root = parser.dir_stack.first().unwrap();
-            assert_eq!(1, root.borrow().subdirs.len());
-            let dir = parser.dir_stack.last().unwrap();
-            assert_eq!("a", dir.borrow().name);
-            assert_eq!(1, dir.borrow().files.len()); //
-            assert_eq!(29116, dir.borrow().files[0].1);
-            assert_eq!("f", dir.borrow().files[0].0);
+            assert_eq!(2, shell.filesystem.children(ROOT).len());
+            assert_eq!("/a", shell.filesystem.path(shell.cwd));
+            let file = shell.filesystem.child(shell.cwd, "f").unwrap();
+            assert_eq!(
+                NodeKind::File { size: 29116 },
+                shell.filesystem.nodes[file].kind
+            );
+            assert_eq!("/a/f", shell.filesystem.path(file));
         }
     }
 
@@ -212,10 +458,148 @@ $ ls
 
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_1_THEN_expected_answers_returned() {
-        assert_eq!(95437, part1(EXAMPLE));
+        let filesystem = FileSystem::from_transcript(EXAMPLE).unwrap();
+        assert_eq!(95437, part1(&filesystem));
     }
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_2_THEN_expected_answers_returned() {
-        assert_eq!(24933642, part2(EXAMPLE));
+        let filesystem = FileSystem::from_transcript(EXAMPLE).unwrap();
+        assert_eq!(Ok(24933642), part2(&filesystem));
+        let overfull = FileSystem::from_transcript("$ cd /\n$ ls\n70000001 a\n").unwrap();
+        assert_eq!(
+            Err(TranscriptError::DiskOverflow { used: 70000001 }),
+            part2(&overfull)
+        );
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_listing_tree_THEN_matches_puzzle() {
+        let filesystem = FileSystem::from_transcript(EXAMPLE).unwrap();
+        assert_eq!(
+            "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - j (file, size=4060174)
+    - d.log (file, size=8033020)
+    - d.ext (file, size=5626152)
+    - k (file, size=7214296)
+",
+            filesystem.tree(ROOT)
+        );
+        let e = filesystem.resolve(ROOT, "/a/e").unwrap();
+        assert_eq!("- e (dir)\n  - i (file, size=584)\n", filesystem.tree(e));
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_querying_sizes_and_paths_THEN_puzzle_totals_found() {
+        let filesystem = FileSystem::from_transcript(EXAMPLE).unwrap();
+        let sizes = filesystem.du();
+        let size_of = |path| sizes[filesystem.resolve(ROOT, path).unwrap()];
+        assert_eq!(584, size_of("/a/e"));
+        assert_eq!(94853, size_of("/a"));
+        assert_eq!(24933642, size_of("d"));
+        assert_eq!(48381165, size_of("/"));
+        let small = filesystem
+            .find_dirs(|size| size <= 100000)
+            .into_iter()
+            .map(|(dir, _)| filesystem.path(dir))
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["/a", "/a/e"], small);
+
+        let a = filesystem.resolve(ROOT, "a").unwrap();
+        assert_eq!(Some(a), filesystem.resolve(a, "e/.."));
+        assert_eq!(
+            filesystem.resolve(ROOT, "/d/k"),
+            filesystem.resolve(a, "../d/./k")
+        );
+        assert_eq!(Some(ROOT), filesystem.resolve(a, "/"));
+        assert_eq!(None, filesystem.resolve(a, "../../.."));
+        assert_eq!(None, filesystem.resolve(ROOT, "a/x"));
+        // files have no children to step into
+        assert_eq!(None, filesystem.resolve(ROOT, "b.txt/x"));
+    }
+
+    #[test]
+    fn GIVEN_revisits_and_relistings_WHEN_replaying_THEN_nothing_counted_twice() {
+        let revisited = format!(
+            "{EXAMPLE}
+$ cd /
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a/e
+$ ls
+584 i
+$ cd /a/e/../..
+$ cd d
+$ cd ..
+"
+        );
+        let filesystem = FileSystem::from_transcript(&revisited).unwrap();
+        assert_eq!(
+            FileSystem::from_transcript(EXAMPLE).unwrap().tree(ROOT),
+            filesystem.tree(ROOT)
+        );
+        assert_eq!(95437, part1(&filesystem));
+        assert_eq!(Ok(24933642), part2(&filesystem));
+    }
+
+    #[test]
+    fn GIVEN_inconsistent_transcripts_WHEN_replaying_THEN_errors_name_the_line() {
+        let error = |contents: &str| FileSystem::from_transcript(contents).err();
+        assert_eq!(
+            Some(TranscriptError::ListingChanged {
+                line: 5,
+                path: "/x".to_owned()
+            }),
+            error("$ cd /\n$ ls\n10 x\n$ ls\n11 x\n")
+        );
+        assert_eq!(
+            Some(TranscriptError::ListingChanged {
+                line: 6,
+                path: "/y".to_owned()
+            }),
+            error("$ cd /\n$ ls\n10 x\n$ ls\n10 x\n20 y\n")
+        );
+        // a directory seen through cd must be listed as one
+        assert_eq!(
+            Some(TranscriptError::ListingChanged {
+                line: 4,
+                path: "/a".to_owned()
+            }),
+            error("$ cd a\n$ cd ..\n$ ls\n5 a\n")
+        );
+        assert_eq!(
+            Some(TranscriptError::NotADirectory {
+                line: 3,
+                path: "/x".to_owned()
+            }),
+            error("$ ls\n10 x\n$ cd x\n")
+        );
+        assert_eq!(
+            Some(TranscriptError::AboveRoot { line: 2 }),
+            error("$ cd /\n$ cd ..\n")
+        );
+        assert_eq!(
+            Some(TranscriptError::UnknownCommand {
+                line: 1,
+                command: "rm -rf /".to_owned()
+            }),
+            error("$ rm -rf /\n")
+        );
+        assert_eq!(
+            Some(TranscriptError::BadEntry { line: 2 }),
+            error("$ ls\nbig x\n")
+        );
     }
 }
@@ -3,75 +3,28 @@ use std::fs;
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        println!("USAGE: {} <filename>", args[0]);
+        println!("USAGE: {} <filename> [--heat-map]", args[0]);
         return;
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
-    println!("Part 1 answer is {}", part1(contents.as_str()));
-    println!("Part 2 answer is {}", part2(contents.as_str()));
+    let survey = Survey::new(&parse(contents.as_str()));
+    if args.get(2).is_some_and(|a| a == "--heat-map") {
+        print!("{}", survey.heat_map_csv());
+    }
+    println!("Part 1 answer is {}", part1(&survey));
+    println!("Part 2 answer is {}", part2(&survey));
 }
 
-fn part1(contents: &str) -> usize {
-    let mut forest = parse(contents);
-    for row_idx in 0..forest.n_rows {
-        walk_line(forest.row_mut_iter(row_idx), |tree: &mut Tree| {
-            tree.vis_east = true
-        });
-        walk_line(forest.row_mut_iter(row_idx).rev(), |tree: &mut Tree| {
-            tree.vis_west = true
-        });
-    }
-    for col_idx in 0..forest.n_cols {
-        walk_line(forest.col_mut_iter(col_idx), |tree: &mut Tree| {
-            tree.vis_north = true
-        });
-        walk_line(forest.col_mut_iter(col_idx).rev(), |tree: &mut Tree| {
-            tree.vis_south = true
-        });
-    }
-    forest
-        .grid
-        .into_iter()
-        .filter(|tree| tree.visible())
-        .count()
+fn part1(survey: &Survey) -> usize {
+    survey.visible_count()
 }
 
-fn part2(contents: &str) -> usize {
-    let forest = parse(contents);
-    let mut max_score = 0;
-    for row_idx in 0..forest.n_rows {
-        for col_idx in 0..forest.n_cols {
-            let mut scenic_score = 1;
-            let tree_height = forest.tree_at(row_idx, col_idx).height;
-            scenic_score *= forest.count_trees(tree_height, row_idx, col_idx, Direction::North);
-            scenic_score *= forest.count_trees(tree_height, row_idx, col_idx, Direction::South);
-            scenic_score *= forest.count_trees(tree_height, row_idx, col_idx, Direction::East);
-            scenic_score *= forest.count_trees(tree_height, row_idx, col_idx, Direction::West);
-            max_score = max_score.max(scenic_score);
-        }
-    }
-    max_score
-}
-
-fn walk_line<'a, IterMut, Setter>(forest_line: IterMut, cb: Setter)
-where
-    IterMut: Iterator<Item = &'a mut Tree>,
-    Setter: Fn(&'a mut Tree),
-{
-    let mut highest: Option<u8> = None;
-    'l1: for tree in forest_line {
-        let tree_height = tree.height;
-        if let Some(h) = highest {
-            if tree_height <= h {
-                continue 'l1;
-            }
-        }
-        highest = Some(tree_height);
-        cb(tree);
-    }
+fn part2(survey: &Survey) -> usize {
+    survey.best_scenic_score()
 }
 
+/// The way a tree looks, and the edge it may be seen from.
 #[derive(Clone, Copy, Debug)]
 enum Direction {
     North,
@@ -80,104 +33,157 @@ enum Direction {
     West,
 }
 
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+    ];
+}
+
 #[derive(Clone, Debug)]
 struct Forest {
-    grid: Vec<Tree>,
+    heights: Vec<u8>,
     n_rows: usize,
     n_cols: usize,
 }
 
 impl Forest {
-    fn new(grid: Vec<Tree>, n_cols: usize) -> Self {
-        let n_rows = grid.len() / n_cols;
-        if n_rows * n_cols != grid.len() {
+    fn new(heights: Vec<u8>, n_cols: usize) -> Self {
+        let n_rows = heights.len() / n_cols;
+        if n_rows * n_cols != heights.len() {
             panic!("not a square grid");
         }
         Self {
-            grid,
+            heights,
             n_rows,
             n_cols,
         }
     }
-    fn tree_at(&self, row_idx: usize, col_idx: usize) -> &Tree {
-        self.grid.get(row_idx * self.n_cols + col_idx).unwrap()
-    }
 
-    fn row_mut_iter(&mut self, row_idx: usize) -> impl DoubleEndedIterator<Item = &mut Tree> {
-        let start = row_idx * self.n_cols;
-        self.grid[start..start + self.n_cols].iter_mut()
+    /// The number of lines of trees that run towards the edge in a direction.
+    fn line_count(&self, dir: Direction) -> usize {
+        match dir {
+            Direction::North | Direction::South => self.n_cols,
+            Direction::East | Direction::West => self.n_rows,
+        }
     }
-    fn col_mut_iter(&mut self, col_idx: usize) -> impl DoubleEndedIterator<Item = &mut Tree> {
-        let start = col_idx;
-        self.grid[start..].iter_mut().step_by(self.n_cols)
+
+    ///
+    /// The grid indices of one row or column, starting at the edge the trees
+    /// look towards in `dir`, so that each tree comes after every tree it
+    /// can see.
+    ///
+    fn line(&self, dir: Direction, line_idx: usize) -> impl Iterator<Item = usize> {
+        let (n_rows, n_cols) = (self.n_rows, self.n_cols);
+        let (start, step, len) = match dir {
+            Direction::North => (line_idx, n_cols as isize, n_rows),
+            Direction::South => (line_idx + (n_rows - 1) * n_cols, -(n_cols as isize), n_rows),
+            Direction::West => (line_idx * n_cols, 1, n_cols),
+            Direction::East => (line_idx * n_cols + n_cols - 1, -1, n_cols),
+        };
+        (0..len).map(move |k| start.wrapping_add_signed(step * k as isize))
     }
-    fn direction_iter(
-        &self,
-        row_idx: usize,
-        col_idx: usize,
-        dir: Direction,
-    ) -> Box<dyn Iterator<Item = &Tree> + '_> {
-        let fwd_skip = row_idx * self.n_cols + col_idx;
-        match dir {
-            Direction::North => {
-                let back_skip = self.grid.len() - fwd_skip - 1;
-                Box::new(self.grid.iter().rev().skip(back_skip).step_by(self.n_cols))
-            }
-            Direction::South => Box::new(self.grid.iter().skip(fwd_skip).step_by(self.n_cols)),
-            Direction::East => {
-                let remain = self.n_cols - col_idx;
-                Box::new(self.grid[fwd_skip..(fwd_skip + remain)].iter())
-            }
-            Direction::West => {
-                Box::new(self.grid[(fwd_skip - col_idx)..(fwd_skip + 1)].iter().rev())
+}
+
+///
+/// What each tree can see: whether it is visible from outside the forest, and
+/// its scenic score, the product of its viewing distances in every direction.
+///
+struct Survey {
+    visible: Vec<bool>,
+    scenic_scores: Vec<usize>,
+    n_cols: usize,
+}
+
+impl Survey {
+    fn new(forest: &Forest) -> Self {
+        let mut survey = Self {
+            visible: vec![false; forest.heights.len()],
+            scenic_scores: vec![1; forest.heights.len()],
+            n_cols: forest.n_cols,
+        };
+        let mut stack = Vec::new();
+        for dir in Direction::ALL {
+            for line_idx in 0..forest.line_count(dir) {
+                survey.sweep(forest, forest.line(dir, line_idx), &mut stack);
             }
         }
+        survey
     }
-    fn count_trees(&self, height: u8, row_idx: usize, col_idx: usize, dir: Direction) -> usize {
-        let mut count = 0;
-        let iter = self.direction_iter(row_idx, col_idx, dir).skip(1);
-        for tree in iter {
-            count += 1;
-            if tree.height >= height {
-                break;
+
+    ///
+    /// Walk a line away from the edge, keeping a stack of the trees that could
+    /// still block the view of a later tree, with their positions. These are
+    /// the trees with no taller or equal tree after them, so their heights
+    /// never rise up the stack.
+    ///
+    /// Each tree first pops the shorter trees, which it hides from everything
+    /// after it. If a tree is left on the stack, it is the nearest one that
+    /// blocks the view. If the stack is empty, nothing blocks the view, so the
+    /// tree sees all the way to the edge and is visible from outside. Every
+    /// tree is pushed and popped at most once.
+    ///
+    fn sweep(
+        &mut self,
+        forest: &Forest,
+        line: impl Iterator<Item = usize>,
+        stack: &mut Vec<(u8, usize)>,
+    ) {
+        stack.clear();
+        for (position, idx) in line.enumerate() {
+            let height = forest.heights[idx];
+            while stack.last().is_some_and(|&(blocker, _)| blocker < height) {
+                stack.pop();
             }
+            let distance = match stack.last() {
+                Some(&(_, blocker_position)) => position - blocker_position,
+                None => {
+                    self.visible[idx] = true;
+                    position
+                }
+            };
+            self.scenic_scores[idx] *= distance;
+            stack.push((height, position));
         }
-        count
     }
-}
 
-#[derive(Clone, Debug)]
-struct Tree {
-    height: u8,
-    vis_north: bool,
-    vis_south: bool,
-    vis_east: bool,
-    vis_west: bool,
-}
+    fn visible_count(&self) -> usize {
+        self.visible.iter().filter(|&&visible| visible).count()
+    }
 
-impl Tree {
-    fn new(height: u8) -> Self {
-        Tree {
-            height,
-            vis_north: false,
-            vis_south: false,
-            vis_east: false,
-            vis_west: false,
-        }
+    fn best_scenic_score(&self) -> usize {
+        self.scenic_scores.iter().copied().max().unwrap_or(0)
     }
-    fn visible(&self) -> bool {
-        self.vis_north || self.vis_south || self.vis_east || self.vis_west
+
+    /// The scenic score of every tree, laid out as the forest is.
+    fn heat_map(&self) -> Vec<Vec<usize>> {
+        self.scenic_scores
+            .chunks(self.n_cols)
+            .map(<[usize]>::to_vec)
+            .collect()
+    }
+
+    fn heat_map_csv(&self) -> String {
+        self.heat_map()
+            .iter()
+            .map(|row| {
+                let cells = row.iter().map(usize::to_string).collect::<Vec<_>>();
+                format!("{}\n", cells.join(","))
+            })
+            .collect()
     }
 }
 
 fn parse(contents: &str) -> Forest {
-    let grid = contents
+    let heights = contents
         .chars()
         .filter(|c| c.is_ascii_digit())
-        .map(|c| Tree::new(c as u8 - b'0'))
+        .map(|c| c as u8 - b'0')
         .collect::<Vec<_>>();
     match contents.find('\n') {
-        Some(len) if len != 0 => Forest::new(grid, len),
+        Some(len) if len != 0 => Forest::new(heights, len),
         _ => panic!("no newline in file"),
     }
 }
@@ -186,25 +192,21 @@ fn parse(contents: &str) -> Forest {
 #[allow(non_snake_case)]
 mod test {
     use super::*;
+    use aoc_common::test_util::XorShift;
 
     #[test]
-    fn GIVEN_small_grid_WHEN_getting_dir_iter_THEN_expected_counts_returned() {
+    fn GIVEN_small_grid_WHEN_getting_lines_THEN_each_starts_at_its_edge() {
         let grid = "123\n345\n567";
         let forest = parse(grid);
         assert_eq!(3, forest.n_cols);
         assert_eq!(3, forest.n_rows);
-        assert_eq!(3, forest.direction_iter(1, 0, Direction::East).count());
-        assert_eq!(2, forest.direction_iter(1, 1, Direction::East).count());
-        assert_eq!(1, forest.direction_iter(1, 2, Direction::East).count());
-        assert_eq!(1, forest.direction_iter(1, 0, Direction::West).count());
-        assert_eq!(2, forest.direction_iter(1, 1, Direction::West).count());
-        assert_eq!(3, forest.direction_iter(1, 2, Direction::West).count());
-        assert_eq!(1, forest.direction_iter(0, 1, Direction::North).count());
-        assert_eq!(2, forest.direction_iter(1, 1, Direction::North).count());
-        assert_eq!(3, forest.direction_iter(2, 1, Direction::North).count());
-        assert_eq!(3, forest.direction_iter(0, 1, Direction::South).count());
-        assert_eq!(2, forest.direction_iter(1, 1, Direction::South).count());
-        assert_eq!(1, forest.direction_iter(2, 1, Direction::South).count());
+        let line = |dir, line_idx| forest.line(dir, line_idx).collect::<Vec<_>>();
+        assert_eq!(vec![3, 4, 5], line(Direction::West, 1));
+        assert_eq!(vec![5, 4, 3], line(Direction::East, 1));
+        assert_eq!(vec![1, 4, 7], line(Direction::North, 1));
+        assert_eq!(vec![7, 4, 1], line(Direction::South, 1));
+        assert_eq!(vec![8, 7, 6], line(Direction::East, 2));
+        assert_eq!(vec![6, 3, 0], line(Direction::South, 0));
     }
 
     static EXAMPLE: &str = r#"30373
@@ -215,10 +217,76 @@ mod test {
 
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_1_THEN_expected_answers_returned() {
-        assert_eq!(21, part1(EXAMPLE));
+        assert_eq!(21, part1(&Survey::new(&parse(EXAMPLE))));
     }
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_2_THEN_expected_answers_returned() {
-        assert_eq!(8, part2(EXAMPLE));
+        assert_eq!(8, part2(&Survey::new(&parse(EXAMPLE))));
+    }
+
+    /// Looks from every tree in every direction in turn.
+    fn brute_force(forest: &Forest) -> (Vec<bool>, Vec<usize>) {
+        let (n_rows, n_cols) = (forest.n_rows as isize, forest.n_cols as isize);
+        let mut visible = Vec::new();
+        let mut scores = Vec::new();
+        for row in 0..n_rows {
+            for col in 0..n_cols {
+                let height = forest.heights[(row * n_cols + col) as usize];
+                let (mut seen, mut score) = (false, 1);
+                for (dr, dc) in [(-1, 0), (1, 0), (0, 1), (0, -1)] {
+                    let (mut r, mut c, mut distance) = (row + dr, col + dc, 0);
+                    let mut blocked = false;
+                    while (0..n_rows).contains(&r) && (0..n_cols).contains(&c) {
+                        distance += 1;
+                        if forest.heights[(r * n_cols + c) as usize] >= height {
+                            blocked = true;
+                            break;
+                        }
+                        r += dr;
+                        c += dc;
+                    }
+                    seen |= !blocked;
+                    score *= distance;
+                }
+                visible.push(seen);
+                scores.push(score);
+            }
+        }
+        (visible, scores)
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_exporting_heat_map_THEN_scores_laid_out_by_tree() {
+        let survey = Survey::new(&parse(EXAMPLE));
+        let heat_map = survey.heat_map();
+        assert_eq!(5, heat_map.len());
+        // the trees the puzzle works through
+        assert_eq!(4, heat_map[1][2]);
+        assert_eq!(8, heat_map[3][2]);
+        // edge trees see nothing in at least one direction
+        assert!(heat_map[0].iter().chain(&heat_map[4]).all(|&s| s == 0));
+        assert_eq!(
+            "0,0,0,0,0\n0,1,4,1,0\n0,6,1,2,0\n0,1,8,3,0\n0,0,0,0,0\n",
+            survey.heat_map_csv()
+        );
+    }
+
+    #[test]
+    fn GIVEN_random_forests_WHEN_surveying_THEN_sweeps_match_brute_force() {
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        for _ in 0..500 {
+            let n_rows = 1 + rng.below(12) as usize;
+            let n_cols = 1 + rng.below(12) as usize;
+            // few heights make equal trees, which block the view, common
+            let max_height = 1 + rng.below(10);
+            let heights = (0..n_rows * n_cols)
+                .map(|_| rng.below(max_height) as u8)
+                .collect::<Vec<_>>();
+            let forest = Forest::new(heights, n_cols);
+            let survey = Survey::new(&forest);
+            let (visible, scores) = brute_force(&forest);
+            assert_eq!(visible, survey.visible, "{forest:?}");
+            assert_eq!(scores, survey.scenic_scores, "{forest:?}");
+        }
     }
 }
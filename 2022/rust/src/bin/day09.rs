@@ -1,36 +1,77 @@
-use std::{collections::HashSet, fs};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    ops::RangeInclusive,
+};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        println!("USAGE: {} <filename>", args[0]);
+        println!("USAGE: {} <filename> [--trace <step>,<step>,...]", args[0]);
         return;
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
+    let trace_steps = match args.get(2).map(String::as_str) {
+        Some("--trace") => args
+            .get(3)
+            .map(|steps| {
+                steps
+                    .split(',')
+                    .map(|step| {
+                        step.parse::<usize>()
+                            .expect("steps are instruction numbers")
+                    })
+                    .collect::<BTreeSet<_>>()
+            })
+            .expect("--trace needs the steps to render"),
+        _ => BTreeSet::new(),
+    };
     println!("Part 1 answer is {}", part1(contents.as_str()));
-    println!("Part 2 answer is {}", part2(contents.as_str()));
+    let part2 = part2(contents.as_str(), |step, (dir, len), rope| {
+        if trace_steps.contains(&step) {
+            let bounds = rope.bounds();
+            println!("== {step}: {dir:?} {len} ==\n\n{}", rope.render(bounds));
+            println!("{}", rope.render_visited(9, bounds));
+        }
+    });
+    println!("Part 2 answer is {part2}");
 }
 
 fn part1(contents: &str) -> usize {
-    solve::<2>(contents)
+    solve(contents, 2, |_, _, _| {})
+}
+
+fn part2(contents: &str, on_instruction: impl FnMut(usize, &(Direction, usize), &Rope)) -> usize {
+    solve(contents, 10, on_instruction)
 }
 
-fn part2(contents: &str) -> usize {
-    solve::<10>(contents)
+fn solve(
+    contents: &str,
+    knots: usize,
+    on_instruction: impl FnMut(usize, &(Direction, usize), &Rope),
+) -> usize {
+    simulate(contents, knots, on_instruction)
+        .visited(knots - 1)
+        .len()
 }
 
-fn solve<const N: usize>(contents: &str) -> usize {
-    let instructions = parse_instructions(contents);
-    let mut rope = Rope::<N>::new();
-    let mut tail_visited = HashSet::<(i32, i32)>::new();
-    for instruction in instructions {
+/// Pull a rope with the given number of knots through the instructions,
+/// calling `on_instruction` after each one finishes with its number, counting
+/// from 1.
+fn simulate(
+    contents: &str,
+    knots: usize,
+    mut on_instruction: impl FnMut(usize, &(Direction, usize), &Rope),
+) -> Rope {
+    let mut rope = Rope::new(knots);
+    for (idx, instruction) in parse_instructions(contents).iter().enumerate() {
         for _ in 0..instruction.1 {
             rope.move_head(instruction.0);
-            tail_visited.insert(rope.vertices[N - 1]);
         }
+        on_instruction(idx + 1, instruction, &rope);
     }
-    tail_visited.len()
+    rope
 }
 
 fn parse_instructions(contents: &str) -> Vec<(Direction, usize)> {
@@ -39,7 +80,7 @@ fn parse_instructions(contents: &str) -> Vec<(Direction, usize)> {
         .filter(|l| !l.trim().is_empty())
         .map(|line| {
             let mut toks = line.split(' ');
-            let dir = Direction::new(toks.next().unwrap().chars().next().unwrap());
+            let dir = Direction::new(toks.next().unwrap());
             let len = toks.next().unwrap().parse::<usize>().unwrap();
             (dir, len)
         })
@@ -47,66 +88,162 @@ fn parse_instructions(contents: &str) -> Vec<(Direction, usize)> {
     instructions
 }
 
+/// The puzzle's four directions, plus diagonals as an extension.
 #[derive(Copy, Debug, Clone)]
 enum Direction {
     L,
     R,
     U,
     D,
+    UL,
+    UR,
+    DL,
+    DR,
 }
 
 impl Direction {
-    fn new(l: char) -> Self {
+    fn new(l: &str) -> Self {
         match l {
-            'L' => Self::L,
-            'R' => Self::R,
-            'U' => Self::U,
-            'D' => Self::D,
-            _ => panic!("unknown direction"),
+            "L" => Self::L,
+            "R" => Self::R,
+            "U" => Self::U,
+            "D" => Self::D,
+            "UL" => Self::UL,
+            "UR" => Self::UR,
+            "DL" => Self::DL,
+            "DR" => Self::DR,
+            _ => panic!("unknown direction {l}"),
+        }
+    }
+
+    /// The step in (x, y), with y increasing downwards as on the page.
+    fn delta(self) -> Pos {
+        match self {
+            Self::L => (-1, 0),
+            Self::R => (1, 0),
+            Self::U => (0, -1),
+            Self::D => (0, 1),
+            Self::UL => (-1, -1),
+            Self::UR => (1, -1),
+            Self::DL => (-1, 1),
+            Self::DR => (1, 1),
         }
     }
 }
 
-struct Rope<const N: usize> {
-    vertices: [(i32, i32); N],
+type Pos = (i32, i32);
+
+/// The inclusive (x, y) ranges of a board to render.
+#[derive(Debug, PartialEq)]
+struct Bounds {
+    x: RangeInclusive<i32>,
+    y: RangeInclusive<i32>,
 }
 
-impl<const N: usize> Rope<N> {
-    fn new() -> Self {
-        let vertices = [(0, 0); N];
-        Self { vertices }
+impl Bounds {
+    /// Grow the board to hold a position.
+    fn extend(&mut self, (x, y): Pos) {
+        self.x = *self.x.start().min(&x)..=*self.x.end().max(&x);
+        self.y = *self.y.start().min(&y)..=*self.y.end().max(&y);
     }
+}
 
-    fn move_head(&mut self, dir: Direction) {
-        let head_pos = &mut self.vertices[0];
-        match dir {
-            Direction::L => head_pos.0 -= 1,
-            Direction::R => head_pos.0 += 1,
-            Direction::U => head_pos.1 -= 1,
-            Direction::D => head_pos.1 += 1,
+/// A rope of any length, starting with every knot at the origin and
+/// remembering every position each knot has visited.
+struct Rope {
+    knots: Vec<Pos>,
+    visited: Vec<HashSet<Pos>>,
+    /// The smallest board holding the start and everywhere any knot has been.
+    bounds: Bounds,
+}
+
+impl Rope {
+    fn new(knots: usize) -> Self {
+        assert!(knots > 0, "a rope needs a head");
+        Self {
+            knots: vec![(0, 0); knots],
+            visited: vec![HashSet::from([(0, 0)]); knots],
+            bounds: Bounds { x: 0..=0, y: 0..=0 },
         }
+    }
+
+    fn move_head(&mut self, dir: Direction) {
+        let (dx, dy) = dir.delta();
+        self.knots[0].0 += dx;
+        self.knots[0].1 += dy;
         self.move_tail();
+        for (knot, visited) in self.knots.iter().zip(self.visited.iter_mut()) {
+            visited.insert(*knot);
+            self.bounds.extend(*knot);
+        }
     }
 
+    /// Each knot that is no longer touching the one ahead steps towards it,
+    /// one place along each axis on which they differ.
     fn move_tail(&mut self) {
-        for i in 1..N {
-            let head_pos = self.vertices[i - 1];
-            let tail_pos = &mut self.vertices[i];
+        for i in 1..self.knots.len() {
+            let head_pos = self.knots[i - 1];
+            let tail_pos = &mut self.knots[i];
 
             let dx = head_pos.0 - tail_pos.0;
             let dy = head_pos.1 - tail_pos.1;
-            if dx.abs() > 1 && dy.abs() > 1 {
-                tail_pos.0 += dx / dx.abs();
-                tail_pos.1 += dy / dy.abs();
-            } else if dx.abs() > 1 {
-                tail_pos.0 += dx / 2;
-                tail_pos.1 = head_pos.1;
-            } else if dy.abs() > 1 {
-                tail_pos.0 = head_pos.0;
-                tail_pos.1 += dy / 2;
+            if dx.abs() > 1 || dy.abs() > 1 {
+                tail_pos.0 += dx.signum();
+                tail_pos.1 += dy.signum();
             }
         }
     }
+
+    fn visited(&self, knot: usize) -> &HashSet<Pos> {
+        &self.visited[knot]
+    }
+
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    /// The head is `H`, the tail of a two-knot rope `T`, and the knots of a
+    /// longer rope are numbered (`#` past 9). Where knots overlap, the one
+    /// nearest the head is shown, and `s` marks the start if nothing covers it.
+    fn label(&self, knot: usize) -> char {
+        match knot {
+            0 => 'H',
+            1 if self.knots.len() == 2 => 'T',
+            1..=9 => char::from_digit(knot as u32, 10).unwrap(),
+            _ => '#',
+        }
+    }
+
+    fn render(&self, bounds: &Bounds) -> String {
+        self.render_with(bounds, |pos| {
+            self.knots
+                .iter()
+                .position(|&k| k == pos)
+                .map(|knot| self.label(knot))
+        })
+    }
+
+    /// The positions visited by one knot as `#`, as in the puzzle's summaries.
+    fn render_visited(&self, knot: usize, bounds: &Bounds) -> String {
+        self.render_with(bounds, |pos| {
+            (pos != (0, 0) && self.visited[knot].contains(&pos)).then_some('#')
+        })
+    }
+
+    fn render_with(&self, bounds: &Bounds, cell: impl Fn(Pos) -> Option<char>) -> String {
+        let mut board = String::new();
+        for y in bounds.y.clone() {
+            for x in bounds.x.clone() {
+                board.push(match cell((x, y)) {
+                    Some(ch) => ch,
+                    None if (x, y) == (0, 0) => 's',
+                    None => '.',
+                });
+            }
+            board.push('\n');
+        }
+        board
+    }
 }
 
 #[cfg(test)]
@@ -130,7 +267,7 @@ R 2
     }
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_2_THEN_expected_answers_returned() {
-        assert_eq!(1, part2(EXAMPLE));
+        assert_eq!(1, part2(EXAMPLE, |_, _, _| {}));
     }
     static EXAMPLE2: &str = r#"
 R 5
@@ -144,6 +281,93 @@ U 20
 "#;
     #[test]
     fn GIVEN_aoc_example2_WHEN_running_part_2_THEN_expected_answers_returned() {
-        assert_eq!(36, part2(EXAMPLE2));
+        assert_eq!(36, part2(EXAMPLE2, |_, _, _| {}));
+    }
+
+    /// The boards at the end of each instruction, joined up.
+    fn walkthrough(contents: &str, knots: usize, bounds: &Bounds) -> (String, Rope) {
+        let mut boards = Vec::new();
+        let rope = simulate(contents, knots, |_, _, rope| {
+            boards.push(rope.render(bounds))
+        });
+        (boards.join("\n"), rope)
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_rendering_two_knots_THEN_matches_walkthrough() {
+        let bounds = Bounds {
+            x: 0..=5,
+            y: -4..=0,
+        };
+        let (boards, rope) = walkthrough(EXAMPLE, 2, &bounds);
+        assert_eq!(
+            boards,
+            "......\n......\n......\n......\ns..TH.\n
+....H.\n....T.\n......\n......\ns.....\n
+.HT...\n......\n......\n......\ns.....\n
+..T...\n.H....\n......\n......\ns.....\n
+......\n....TH\n......\n......\ns.....\n
+......\n....T.\n.....H\n......\ns.....\n
+......\n......\nHT....\n......\ns.....\n
+......\n......\n.TH...\n......\ns.....\n"
+        );
+        assert_eq!(
+            rope.render_visited(1, &bounds),
+            "..##..\n...##.\n.####.\n....#.\ns###..\n"
+        );
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_rendering_ten_knots_THEN_matches_walkthrough() {
+        let bounds = Bounds {
+            x: 0..=5,
+            y: -4..=0,
+        };
+        let (boards, rope) = walkthrough("R 4\nU 4", 10, &bounds);
+        assert_eq!(
+            boards,
+            "......\n......\n......\n......\n4321H.\n
+....H.\n....1.\n..432.\n.5....\n6.....\n"
+        );
+        // knot 5 is pulled one place diagonally, while 6 onwards never move
+        assert_eq!(2, rope.visited(5).len());
+        assert_eq!(1, rope.visited(6).len());
+        assert_eq!(1, rope.visited(9).len());
+    }
+
+    #[test]
+    fn GIVEN_aoc_example2_WHEN_rendering_tail_trail_THEN_visited_positions_drawn() {
+        let rope = simulate(EXAMPLE2, 10, |_, _, _| {});
+        let trail = rope.render_visited(9, rope.bounds());
+        assert_eq!(35, trail.matches('#').count());
+        assert_eq!(1, trail.matches('s').count());
+        // the board is the walkthrough's, with the start 11 from the left
+        // and 5 from the bottom
+        let expected = Bounds {
+            x: -11..=14,
+            y: -15..=5,
+        };
+        assert_eq!(&expected, rope.bounds());
+        // every knot visits at least as many places as the one behind it
+        for knot in 1..10 {
+            assert!(rope.visited(knot - 1).len() >= rope.visited(knot).len());
+        }
+    }
+
+    #[test]
+    fn GIVEN_diagonal_moves_WHEN_pulling_rope_THEN_knots_follow_diagonally() {
+        let rope = simulate("UR 3\nDR 1", 3, |_, _, _| {});
+        assert_eq!(vec![(4, -2), (3, -2), (2, -2)], rope.knots);
+        assert_eq!(
+            HashSet::from([(0, 0), (1, -1), (2, -2), (3, -2)]),
+            *rope.visited(1)
+        );
+        assert_eq!(
+            "..21H\n.....\ns....\n",
+            rope.render(&Bounds {
+                x: 0..=4,
+                y: -2..=0
+            })
+        );
     }
 }
@@ -1,67 +1,241 @@
-use std::fs;
+use aoc_common::ocr::{self, OcrError};
+use std::{collections::BTreeSet, fmt, fs};
 
 /* start with line:
 
-~/dev/advent_of_code/2022/rust/target/debug/day10 /home/stu/dev/advent_of_code/2022/input/day10.dat
+~/dev/advent_of_code/2022/rust/target/debug/day10 /home/stu/dev/advent_of_code/2022/input/day10.dat [--trace]
 
 */
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        println!("USAGE: {} <filename>", args[0]);
+        println!("USAGE: {} <filename> [--trace]", args[0]);
         return;
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
-    println!("Part 1 answer is {}", part1(contents.as_str()));
-    println!("Part 2 answer is:");
-    let lines = part2(contents.as_str());
-    lines.iter().for_each(|line| println!("{}", line));
+    let trace = args.get(2).is_some_and(|a| a == "--trace");
+    match part1(contents.as_str()) {
+        Ok(answer) => println!("Part 1 answer is {answer}"),
+        Err(e) => println!("Part 1 failed: {e}"),
+    }
+    let on_cycle = |cycle: &Cycle, crt: &Crt| {
+        if trace {
+            let (column, row) = crt.beam();
+            println!("{cycle} | beam ({column}, {row})");
+        }
+    };
+    let on_screen = |screen: &[String]| {
+        if trace {
+            screen.iter().for_each(|line| println!("{}", line));
+        }
+    };
+    match part2(contents.as_str(), on_cycle, on_screen) {
+        Ok(answer) => println!("Part 2 answer is {answer}"),
+        Err(e) => println!("Part 2 failed: {e}"),
+    }
+}
+
+/// The cycles during which part 1 samples the signal strength.
+const SAMPLED_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
+
+fn part1(contents: &str) -> Result<i32, EmulatorError> {
+    let program = parse_program(contents)?;
+    let mut debugger = Debugger::new(&program);
+    debugger.watch("cycle * x")?;
+    SAMPLED_CYCLES
+        .iter()
+        .for_each(|&cycle| debugger.break_at(cycle));
+    let mut total = 0;
+    while let Some(cycle) = debugger.resume() {
+        total += debugger.watch_values(&cycle)[0].1;
+    }
+    Ok(total)
+}
+
+/// Draw the screen, calling `on_cycle` as [`draw`] does and `on_screen` with
+/// the finished picture, then read the letters on it.
+fn part2(
+    contents: &str,
+    on_cycle: impl FnMut(&Cycle, &Crt),
+    on_screen: impl FnOnce(&[String]),
+) -> Result<String, EmulatorError> {
+    let screen = draw(contents, on_cycle)?;
+    on_screen(&screen);
+    read_screen(&screen)
 }
 
-fn part1(_contents: &str) -> i32 {
-    let instructions = parse_instructions(_contents);
-    let observed_cycles = vec![20, 60, 100, 140, 180, 220];
-    let mut cpu = Cpu::new();
-    let observations = cpu.execute(&instructions, &observed_cycles, None);
-    observations.into_iter().sum()
+fn read_screen(screen: &[String]) -> Result<String, EmulatorError> {
+    ocr::read_letters(screen).map_err(EmulatorError::Unreadable)
 }
 
-fn part2(_contents: &str) -> Vec<String> {
-    let instructions = parse_instructions(_contents);
-    let mut cpu = Cpu::new();
+/// Run the program with the CRT's beam sweeping one pixel per cycle, calling
+/// `on_cycle` just before each pixel is drawn.
+fn draw(
+    contents: &str,
+    mut on_cycle: impl FnMut(&Cycle, &Crt),
+) -> Result<Vec<String>, EmulatorError> {
+    let program = parse_program(contents)?;
+    let mut cpu = Cpu::new(&program);
     let mut crt = Crt::new(40, 6);
-    let _ = cpu.execute(&instructions, &Vec::new(), Some(&mut crt));
-    crt.to_strings()
+    while let Some(cycle) = cpu.step() {
+        on_cycle(&cycle, &crt);
+        crt.draw(cycle.registers[X]);
+    }
+    Ok(crt.to_strings())
 }
 
-fn parse_instructions(_contents: &str) -> Vec<Instruction> {
-    let instructions = _contents
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(Instruction::new)
-        .collect::<Vec<_>>();
-    instructions
+#[derive(Debug, Eq, PartialEq)]
+enum EmulatorError {
+    UnknownInstruction { line: usize, mnemonic: String },
+    MissingOperand { line: usize },
+    BadOperand { line: usize, operand: String },
+    BadWatch(String),
+    Unreadable(OcrError),
 }
 
-#[allow(non_camel_case_types)]
-enum Instruction {
-    nop,
-    addx(i32),
+impl fmt::Display for EmulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownInstruction { line, mnemonic } => {
+                write!(f, "unknown instruction \"{mnemonic}\" on line {line}")
+            }
+            Self::MissingOperand { line } => write!(f, "missing operand on line {line}"),
+            Self::BadOperand { line, operand } => {
+                write!(f, "bad operand \"{operand}\" on line {line}")
+            }
+            Self::BadWatch(expr) => write!(f, "can't watch \"{expr}\""),
+            Self::Unreadable(e) => write!(f, "can't read the screen: {e}"),
+        }
+    }
+}
+
+/// The register file, named `w` to `z`. Only `x` is used by the puzzle.
+const REGISTER_NAMES: [char; 4] = ['w', 'x', 'y', 'z'];
+const X: usize = 1;
+
+type Registers = [i32; REGISTER_NAMES.len()];
+
+///
+/// An entry in the instruction table: how many cycles the instruction takes,
+/// and what it does to its register once the last of them ends.
+///
+/// Instructions that work on a register name it as the last letter of their
+/// mnemonic, so `add` is written `addx`, `addy` and so on.
+///
+struct Opcode {
+    name: &'static str,
+    cycles: usize,
+    takes_register: bool,
+    effect: fn(i32, i32) -> i32,
+}
+
+const INSTRUCTION_SET: [Opcode; 4] = [
+    Opcode {
+        name: "noop",
+        cycles: 1,
+        takes_register: false,
+        effect: |value, _| value,
+    },
+    Opcode {
+        name: "add",
+        cycles: 2,
+        takes_register: true,
+        effect: |value, operand| value + operand,
+    },
+    Opcode {
+        name: "set",
+        cycles: 1,
+        takes_register: true,
+        effect: |_, operand| operand,
+    },
+    Opcode {
+        name: "mul",
+        cycles: 3,
+        takes_register: true,
+        effect: |value, operand| value * operand,
+    },
+];
+
+struct Instruction {
+    opcode: &'static Opcode,
+    register: Option<usize>,
+    operand: i32,
 }
 
 impl Instruction {
-    fn new(line: &str) -> Self {
-        let toks = line.split(' ').collect::<Vec<_>>();
-        match toks[0] {
-            "noop" => Self::nop,
-            "addx" => Self::addx(toks[1].parse::<i32>().unwrap()),
-            _ => panic!("unknown instruction \"{}\"", toks[0]),
+    fn new(line: &str, line_number: usize) -> Result<Self, EmulatorError> {
+        let mut toks = line.split_whitespace();
+        let mnemonic = toks.next().unwrap_or_default();
+        let unknown = || EmulatorError::UnknownInstruction {
+            line: line_number,
+            mnemonic: mnemonic.to_owned(),
+        };
+        let (opcode, register) = INSTRUCTION_SET
+            .iter()
+            .find_map(|opcode| {
+                let suffix = mnemonic.strip_prefix(opcode.name)?;
+                match (opcode.takes_register, suffix.as_bytes()) {
+                    (false, []) => Some((opcode, None)),
+                    (true, &[name]) => {
+                        let register = REGISTER_NAMES.iter().position(|&r| r == name as char)?;
+                        Some((opcode, Some(register)))
+                    }
+                    _ => None,
+                }
+            })
+            .ok_or_else(unknown)?;
+        let operand = match register {
+            None => 0,
+            Some(_) => {
+                let operand = toks
+                    .next()
+                    .ok_or(EmulatorError::MissingOperand { line: line_number })?;
+                operand
+                    .parse::<i32>()
+                    .map_err(|_| EmulatorError::BadOperand {
+                        line: line_number,
+                        operand: operand.to_owned(),
+                    })?
+            }
+        };
+        if let Some(extra) = toks.next() {
+            return Err(EmulatorError::BadOperand {
+                line: line_number,
+                operand: extra.to_owned(),
+            });
+        }
+        Ok(Self {
+            opcode,
+            register,
+            operand,
+        })
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.register {
+            Some(r) => write!(
+                f,
+                "{}{} {}",
+                self.opcode.name, REGISTER_NAMES[r], self.operand
+            ),
+            None => write!(f, "{}", self.opcode.name),
         }
     }
 }
 
+fn parse_program(contents: &str) -> Result<Vec<Instruction>, EmulatorError> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(idx, l)| Instruction::new(l, idx + 1))
+        .collect()
+}
+
 struct Crt {
     x_position: usize,
     y_position: usize,
@@ -77,12 +251,15 @@ impl Crt {
             y_position: 0,
             n_columns,
             n_rows,
-            pixels: vec![' '; n_rows * n_columns + 1],
+            pixels: vec![' '; n_rows * n_columns],
         }
     }
-    fn advance(&mut self, x_register: i32) {
+
+    /// Light the pixel under the beam if the three-pixel sprite centred on
+    /// `sprite` covers it, then move the beam on.
+    fn draw(&mut self, sprite: i32) {
         let pixel = self.x_position + self.y_position * self.n_columns;
-        self.pixels[pixel] = match (self.x_position as i32 - x_register).abs() {
+        self.pixels[pixel] = match (self.x_position as i32 - sprite).abs() {
             distance if distance < 2 => '#',
             _ => ' ',
         };
@@ -92,6 +269,12 @@ impl Crt {
             self.y_position = (self.y_position + 1) % self.n_rows;
         }
     }
+
+    /// The (column, row) the beam will draw next.
+    fn beam(&self) -> (usize, usize) {
+        (self.x_position, self.y_position)
+    }
+
     fn to_strings(&self) -> Vec<String> {
         self.pixels
             .chunks(self.n_columns)
@@ -100,42 +283,159 @@ impl Crt {
     }
 }
 
-struct Cpu {
-    x_register: i32,
+/// What the CPU was doing during one cycle, with the registers as they were
+/// before any instruction finishing at the end of it took effect.
+struct Cycle<'p> {
+    number: usize,
+    pc: usize,
+    instruction: &'p Instruction,
+    registers: Registers,
+}
+
+impl fmt::Display for Cycle<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cycle {:4} | pc {:4} {:10} |",
+            self.number,
+            self.pc,
+            self.instruction.to_string()
+        )?;
+        for (name, value) in REGISTER_NAMES.iter().zip(self.registers) {
+            write!(f, " {}={value}", name.to_ascii_uppercase())?;
+        }
+        Ok(())
+    }
+}
+
+struct Cpu<'p> {
+    program: &'p [Instruction],
+    registers: Registers,
     cycle: usize,
+    pc: usize,
+    /// The cycles spent so far on the instruction at `pc`.
+    elapsed: usize,
 }
 
-impl Cpu {
-    fn new() -> Self {
+impl<'p> Cpu<'p> {
+    fn new(program: &'p [Instruction]) -> Self {
+        let mut registers = Registers::default();
+        registers[X] = 1;
         Self {
-            x_register: 1,
+            program,
+            registers,
             cycle: 0,
+            pc: 0,
+            elapsed: 0,
         }
     }
-    fn execute(
-        &mut self,
-        program: &[Instruction],
-        observed_cycles: &[usize],
-        mut crt: Option<&mut Crt>,
-    ) -> Vec<i32> {
-        let mut watch_points = Vec::<i32>::new();
-        program.iter().for_each(|instruction| {
-            let (n_cycles, value_change) = match instruction {
-                Instruction::addx(value) => (2, *value),
-                Instruction::nop => (1, 0),
-            };
-            for _i in 0..n_cycles {
-                if let Some(ref mut crt) = crt {
-                    crt.advance(self.x_register);
-                }
-                self.cycle += 1;
-                if observed_cycles.contains(&self.cycle) {
-                    watch_points.push(self.cycle as i32 * self.x_register);
-                }
+
+    /// Run a single cycle, or return `None` once the program has finished.
+    fn step(&mut self) -> Option<Cycle<'p>> {
+        let instruction = self.program.get(self.pc)?;
+        self.cycle += 1;
+        let during = Cycle {
+            number: self.cycle,
+            pc: self.pc,
+            instruction,
+            registers: self.registers,
+        };
+        self.elapsed += 1;
+        if self.elapsed == instruction.opcode.cycles {
+            if let Some(r) = instruction.register {
+                self.registers[r] =
+                    (instruction.opcode.effect)(self.registers[r], instruction.operand);
             }
-            self.x_register += value_change;
-        });
-        watch_points
+            self.pc += 1;
+            self.elapsed = 0;
+        }
+        Some(during)
+    }
+}
+
+/// An integer expression over the cycle number, the program counter and the
+/// registers, made of sums of products such as `cycle * x + -1`.
+enum Expr {
+    Cycle,
+    Pc,
+    Register(usize),
+    Const(i32),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn parse(text: &str) -> Result<Self, EmulatorError> {
+        let bad = || EmulatorError::BadWatch(text.to_owned());
+        let atom = |atom: &str| match atom.trim() {
+            "cycle" => Ok(Self::Cycle),
+            "pc" => Ok(Self::Pc),
+            name => match REGISTER_NAMES.iter().position(|&r| name.chars().eq([r])) {
+                Some(r) => Ok(Self::Register(r)),
+                None => name.parse::<i32>().map(Self::Const).map_err(|_| bad()),
+            },
+        };
+        let term = |term: &str| {
+            term.split('*')
+                .map(atom)
+                .reduce(|a, b| Ok(Self::Mul(Box::new(a?), Box::new(b?))))
+                .unwrap()
+        };
+        text.split('+')
+            .map(term)
+            .reduce(|a, b| Ok(Self::Add(Box::new(a?), Box::new(b?))))
+            .unwrap()
+    }
+
+    fn eval(&self, cycle: &Cycle) -> i32 {
+        match self {
+            Self::Cycle => cycle.number as i32,
+            Self::Pc => cycle.pc as i32,
+            Self::Register(r) => cycle.registers[*r],
+            Self::Const(value) => *value,
+            Self::Add(a, b) => a.eval(cycle) + b.eval(cycle),
+            Self::Mul(a, b) => a.eval(cycle) * b.eval(cycle),
+        }
+    }
+}
+
+/// A CPU that can be stopped during chosen cycles to read watch expressions.
+struct Debugger<'p> {
+    cpu: Cpu<'p>,
+    breakpoints: BTreeSet<usize>,
+    watches: Vec<(String, Expr)>,
+}
+
+impl<'p> Debugger<'p> {
+    fn new(program: &'p [Instruction]) -> Self {
+        Self {
+            cpu: Cpu::new(program),
+            breakpoints: BTreeSet::new(),
+            watches: Vec::new(),
+        }
+    }
+
+    fn break_at(&mut self, cycle: usize) {
+        self.breakpoints.insert(cycle);
+    }
+
+    fn watch(&mut self, text: &str) -> Result<(), EmulatorError> {
+        self.watches.push((text.to_owned(), Expr::parse(text)?));
+        Ok(())
+    }
+
+    /// Run on to the next breakpoint, returning the cycle it stopped during,
+    /// or `None` if the program finishes first.
+    fn resume(&mut self) -> Option<Cycle<'p>> {
+        std::iter::from_fn(|| self.cpu.step())
+            .find(|cycle| self.breakpoints.contains(&cycle.number))
+    }
+
+    fn watch_values(&self, cycle: &Cycle) -> Vec<(&str, i32)> {
+        self.watches
+            .iter()
+            .map(|(text, expr)| (text.as_str(), expr.eval(cycle)))
+            .collect()
     }
 }
 
@@ -295,23 +595,163 @@ noop
 
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_1_THEN_expected_answers_returned() {
-        assert_eq!(13140, part1(EXAMPLE));
+        assert_eq!(Ok(13140), part1(EXAMPLE));
     }
     #[test]
-    fn GIVEN_aoc_example_WHEN_running_part_2_THEN_expected_answers_returned() {
+    fn GIVEN_aoc_example_WHEN_drawing_THEN_expected_screen_returned() {
         let expected = r#"##..##..##..##..##..##..##..##..##..##..
 ###...###...###...###...###...###...###.
 ####....####....####....####....####....
 #####.....#####.....#####.....#####.....
 ######......######......######......####
-#######.......#######.......#######.....
- "#;
+#######.......#######.......#######....."#;
         let expected = expected
             .replace('.', " ")
             .split('\n')
             .map(str::to_owned)
             .collect::<Vec<_>>();
 
-        assert_eq!(expected, part2(EXAMPLE));
+        assert_eq!(Ok(expected), draw(EXAMPLE, |_, _| {}));
+        // the example draws stripes rather than letters
+        assert!(matches!(
+            part2(EXAMPLE, |_, _| {}, |_| {}),
+            Err(EmulatorError::Unreadable(OcrError::UnknownGlyph {
+                position: 0,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn GIVEN_program_drawing_letters_WHEN_running_part_2_THEN_letters_read() {
+        let screen = [
+            "###  #### #  # ####  ##    ##  ##  ###  ",
+            "#  #    # #  # #    #  #    # #  # #  # ",
+            "#  #   #  #### ###  #       # #    ###  ",
+            "###   #   #  # #    # ##    # #    #  # ",
+            "# #  #    #  # #    #  # #  # #  # #  # ",
+            "#  # #### #  # #     ###  ##   ##  ###  ",
+        ];
+        // X starts at 1, lighting the first pixel, and each one-cycle setx
+        // places the sprite for the pixel drawn during the next cycle, either
+        // over it or well clear of it
+        let pixels = screen.concat().into_bytes();
+        let program = (1..pixels.len())
+            .map(|pixel| {
+                let column = (pixel % 40) as i32;
+                let sprite = if pixels[pixel] == b'#' {
+                    column
+                } else {
+                    column + 10
+                };
+                format!("setx {sprite}\n")
+            })
+            .chain(["noop\n".to_owned()])
+            .collect::<String>();
+        assert_eq!(
+            Ok(screen.map(str::to_owned).to_vec()),
+            draw(&program, |_, _| {})
+        );
+        assert_eq!(
+            Ok("RZHFGJCB".to_owned()),
+            part2(&program, |_, _| {}, |_| {})
+        );
+    }
+
+    const SMALL: &str = "noop\naddx 3\naddx -5\n";
+
+    fn trace(program: &[Instruction]) -> Vec<(usize, i32)> {
+        let mut cpu = Cpu::new(program);
+        std::iter::from_fn(|| cpu.step())
+            .map(|cycle| (cycle.number, cycle.registers[X]))
+            .collect()
+    }
+
+    #[test]
+    fn GIVEN_small_example_WHEN_stepping_THEN_x_changes_after_each_addx_finishes() {
+        let program = parse_program(SMALL).unwrap();
+        assert_eq!(
+            vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)],
+            trace(&program)
+        );
+        let mut cpu = Cpu::new(&program);
+        while cpu.step().is_some() {}
+        assert_eq!(-1, cpu.registers[X]);
+        assert_eq!(None, cpu.step().map(|cycle| cycle.number));
+    }
+
+    #[test]
+    fn GIVEN_other_registers_WHEN_running_THEN_instruction_table_applied() {
+        let program = parse_program("setw 7\naddy -2\nmulw 3\nnoop\n").unwrap();
+        let mut cpu = Cpu::new(&program);
+        let cycles = std::iter::from_fn(|| cpu.step()).collect::<Vec<_>>();
+        // 1 cycle for set, 2 for add, 3 for mul and 1 for noop
+        assert_eq!(7, cycles.len());
+        assert_eq!([7, 1, -2, 0], cycles[3].registers);
+        assert_eq!([21, 1, -2, 0], cpu.registers);
+        assert_eq!(
+            "cycle    4 | pc    2 mulw 3     | W=7 X=1 Y=-2 Z=0",
+            cycles[3].to_string()
+        );
+    }
+
+    #[test]
+    fn GIVEN_bad_programs_WHEN_parsing_THEN_errors_name_the_line() {
+        let error = |contents| parse_program(contents).err();
+        assert_eq!(
+            Some(EmulatorError::UnknownInstruction {
+                line: 2,
+                mnemonic: "addq".to_owned()
+            }),
+            error("noop\naddq 3\n")
+        );
+        assert_eq!(
+            Some(EmulatorError::UnknownInstruction {
+                line: 1,
+                mnemonic: "noopx".to_owned()
+            }),
+            error("noopx\n")
+        );
+        assert_eq!(
+            Some(EmulatorError::MissingOperand { line: 3 }),
+            error("noop\n\naddx\n")
+        );
+        assert_eq!(
+            Some(EmulatorError::BadOperand {
+                line: 1,
+                operand: "three".to_owned()
+            }),
+            error("addx three\n")
+        );
+        assert_eq!(
+            Some(EmulatorError::BadOperand {
+                line: 1,
+                operand: "1".to_owned()
+            }),
+            error("noop 1\n")
+        );
+    }
+
+    #[test]
+    fn GIVEN_breakpoints_WHEN_resuming_THEN_stops_during_those_cycles_with_watches() {
+        let program = parse_program(EXAMPLE).unwrap();
+        let mut debugger = Debugger::new(&program);
+        debugger.watch("x").unwrap();
+        debugger.watch("cycle*x").unwrap();
+        debugger.watch("pc + cycle * 2 + -1").unwrap();
+        for cycle in [220, 20, 60] {
+            debugger.break_at(cycle);
+        }
+        let mut stops = Vec::new();
+        while let Some(cycle) = debugger.resume() {
+            let values = debugger.watch_values(&cycle);
+            assert_eq!(cycle.pc as i32 + cycle.number as i32 * 2 - 1, values[2].1);
+            stops.push((cycle.number, values[0].1, values[1].1));
+        }
+        assert_eq!(vec![(20, 21, 420), (60, 19, 1140), (220, 18, 3960)], stops);
+        assert_eq!(
+            Some(EmulatorError::BadWatch("cycle * q".to_owned())),
+            debugger.watch("cycle * q").err()
+        );
     }
 }
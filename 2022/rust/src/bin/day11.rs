@@ -1,32 +1,70 @@
-use std::fs;
+use num::{BigUint, Integer};
+use std::{collections::VecDeque, fmt, fmt::Write, fs};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        println!("USAGE: {} <filename>", args[0]);
+        println!("USAGE: {} <filename> [--verbose] [--exact]", args[0]);
         return;
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
-    println!("Part 1 answer is {}", part1(contents.as_str()));
+    let verbose = args[2..].iter().any(|a| a == "--verbose");
+    let exact = args[2..].iter().any(|a| a == "--exact");
+
+    let part1 = part1(contents.as_str(), |round, narrative, simulation| {
+        if verbose {
+            print!("{narrative}");
+            println!(
+                "\nAfter round {round}, the monkeys are holding items with these worry levels:"
+            );
+            print!("{}", simulation.holdings());
+            println!();
+            if round == PART1_ROUNDS {
+                print!("{}", simulation.inspections());
+            }
+        }
+    });
+    println!("Part 1 answer is {part1}");
+    if exact {
+        // squaring monkeys grow the exact levels to millions of digits, so
+        // this takes a while
+        let exact = run_simulation::<BigUint>(contents.as_str(), PART1_ROUNDS, 3, Reduction::Exact);
+        match exact == part1 {
+            true => println!("The exact worry levels give the same answer"),
+            false => println!("The exact worry levels give {exact} instead"),
+        }
+    }
     println!("Part 2 answer is {}", part2(contents.as_str()));
 }
 
-fn part1(_contents: &str) -> usize {
-    let monkeys = parse(_contents);
-    run_simulation(monkeys, 20, 3)
+const PART1_ROUNDS: usize = 20;
+
+/// Run the rounds with relief, calling `on_round` after each one with its
+/// number, counting from 1, and the puzzle's narrative of it.
+fn part1(contents: &str, mut on_round: impl FnMut(usize, &str, &Simulation<u64>)) -> usize {
+    let mut simulation = Simulation::<u64>::new(parse(contents), 3, Reduction::Modular);
+    let mut narrative = String::new();
+    for round in 1..=PART1_ROUNDS {
+        narrative.clear();
+        simulation.round(Some(&mut narrative));
+        on_round(round, &narrative, &simulation);
+    }
+    simulation.monkey_business()
 }
 
-fn part2(_contents: &str) -> usize {
-    let monkeys = parse(_contents);
-    run_simulation(monkeys, 10000, 1)
+fn part2(contents: &str) -> usize {
+    run_simulation::<u64>(contents, 10000, 1, Reduction::Modular)
 }
 
-fn parse(contents: &str) -> Vec<Monkey> {
-    let mut monkeys = Vec::<Monkey>::new();
+fn parse<W: From<u64>>(contents: &str) -> Vec<Monkey<W>> {
+    let mut monkeys = Vec::<Monkey<W>>::new();
     let mut group = Vec::<&str>::new();
-    for (idx, line) in contents.lines().enumerate() {
+    for (idx, line) in contents.lines().chain([""]).enumerate() {
         if line.trim().is_empty() {
+            if group.is_empty() {
+                continue;
+            }
             if group.len() != 6 {
                 panic!("Monkey group is not 6 lines at {idx}");
             }
@@ -42,76 +80,273 @@ fn parse(contents: &str) -> Vec<Monkey> {
     monkeys
 }
 
-fn run_simulation(mut monkeys: Vec<Monkey>, n_rounds: usize, divisor: usize) -> usize {
-    let common_multiple = monkeys.iter().map(|m| m.test_divisor).product();
-    execute_rounds(n_rounds, &mut monkeys, divisor, common_multiple);
-    monkeys.sort_by_key(|monkey| monkey.inspections);
-    monkeys
-        .iter()
-        .rev()
-        .take(2)
-        .map(|m| m.inspections)
-        .product()
+fn run_simulation<W>(contents: &str, n_rounds: usize, relief: u64, reduction: Reduction) -> usize
+where
+    W: Integer + Clone + fmt::Display + From<u64>,
+{
+    let mut simulation = Simulation::<W>::new(parse(contents), relief, reduction);
+    for _round in 0..n_rounds {
+        simulation.round(None);
+    }
+    simulation.monkey_business()
 }
 
-fn execute_rounds(n_rounds: usize, monkeys: &mut Vec<Monkey>, divisor: usize, subtractor: usize) {
-    for _round in 0..n_rounds {
-        for idx in 0..monkeys.len() {
-            while let Some(result) = monkeys[idx].inspect_item(divisor, subtractor) {
-                monkeys[result.destination].items.push(result.item);
+///
+/// The expression on the right of an operation line, such as `old * 19`.
+///
+/// Operators bind as usual, `*` and `/` before `+` and `-`, and otherwise
+/// work from left to right.
+///
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Old,
+    Const(u64),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn parse(text: &str) -> Self {
+        let mut tokens = text.split_whitespace();
+        let atom = |token: Option<&str>| match token {
+            Some("old") => Expr::Old,
+            Some(n) => Expr::Const(n.parse::<u64>().expect("non-integer argument")),
+            None => panic!("expression \"{text}\" ends with an operator"),
+        };
+        // each term as the operator joining it to the sum so far and its factors
+        let mut terms = vec![("+", vec![("*", atom(tokens.next()))])];
+        while let Some(op) = tokens.next() {
+            match op {
+                "+" | "-" => terms.push((op, vec![("*", atom(tokens.next()))])),
+                "*" | "/" => terms.last_mut().unwrap().1.push((op, atom(tokens.next()))),
+                _ => panic!("unknown operator {op}"),
+            }
+        }
+        let fold = |items: Vec<(&str, Expr)>| {
+            items
+                .into_iter()
+                .reduce(|(_, a), (op, b)| {
+                    let (a, b) = (Box::new(a), Box::new(b));
+                    let combined = match op {
+                        "+" => Expr::Add(a, b),
+                        "-" => Expr::Sub(a, b),
+                        "*" => Expr::Mul(a, b),
+                        _ => Expr::Div(a, b),
+                    };
+                    ("", combined)
+                })
+                .unwrap()
+                .1
+        };
+        fold(
+            terms
+                .into_iter()
+                .map(|(op, factors)| (op, fold(factors)))
+                .collect(),
+        )
+    }
+
+    fn eval<W: Integer + Clone + From<u64>>(&self, old: &W) -> W {
+        match self {
+            Expr::Old => old.clone(),
+            Expr::Const(n) => W::from(*n),
+            Expr::Add(a, b) => a.eval(old) + b.eval(old),
+            Expr::Sub(a, b) => a.eval(old) - b.eval(old),
+            Expr::Mul(a, b) => a.eval(old) * b.eval(old),
+            Expr::Div(a, b) => a.eval(old) / b.eval(old),
+        }
+    }
+
+    /// How the puzzle's narrative describes the change to the worry level, for
+    /// the forms it uses, e.g. "is multiplied by itself".
+    fn describe(&self) -> Option<String> {
+        let (verb, a, b) = match self {
+            Expr::Add(a, b) => ("increases by", a, b),
+            Expr::Sub(a, b) => ("decreases by", a, b),
+            Expr::Mul(a, b) => ("is multiplied by", a, b),
+            Expr::Div(a, b) => ("is divided by", a, b),
+            _ => return None,
+        };
+        match (a.as_ref(), b.as_ref()) {
+            (Expr::Old, Expr::Old) => Some(format!("{verb} itself")),
+            (Expr::Old, Expr::Const(n)) => Some(format!("{verb} {n}")),
+            _ => None,
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Add(..) | Expr::Sub(..) => 1,
+            Expr::Mul(..) | Expr::Div(..) => 2,
+            _ => 3,
+        }
+    }
+
+    /// Whether the result modulo any number only depends on `old` modulo that
+    /// number, as it does when the expression only adds and multiplies.
+    fn is_modular(&self) -> bool {
+        match self {
+            Expr::Old | Expr::Const(_) => true,
+            Expr::Add(a, b) | Expr::Mul(a, b) => a.is_modular() && b.is_modular(),
+            Expr::Sub(..) | Expr::Div(..) => false,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (op, a, b) = match self {
+            Expr::Old => return write!(f, "old"),
+            Expr::Const(n) => return write!(f, "{n}"),
+            Expr::Add(a, b) => ("+", a, b),
+            Expr::Sub(a, b) => ("-", a, b),
+            Expr::Mul(a, b) => ("*", a, b),
+            Expr::Div(a, b) => ("/", a, b),
+        };
+        // the right operand needs brackets at equal precedence too, as the
+        // operators associate to the left
+        match a.precedence() < self.precedence() {
+            true => write!(f, "({a}) {op} ")?,
+            false => write!(f, "{a} {op} ")?,
+        }
+        match b.precedence() <= self.precedence() {
+            true => write!(f, "({b})"),
+            false => write!(f, "{b}"),
+        }
+    }
+}
+
+/// How worry levels are kept from growing without bound.
+#[derive(Clone, Copy)]
+enum Reduction {
+    /// Keep each worry level modulo the LCM of the monkeys' test divisors.
+    /// Without relief this leaves every test unchanged, but dividing the
+    /// reduced level for relief can give a different result. Operations may
+    /// only add and multiply, as subtracting could take a reduced level below
+    /// zero.
+    Modular,
+    /// Keep the worry levels exactly, for arbitrary-precision types.
+    Exact,
+}
+
+struct Simulation<W> {
+    monkeys: Vec<Monkey<W>>,
+    relief: u64,
+    modulus: Option<u64>,
+}
+
+impl<W: Integer + Clone + fmt::Display + From<u64>> Simulation<W> {
+    fn new(monkeys: Vec<Monkey<W>>, relief: u64, reduction: Reduction) -> Self {
+        let modulus = match reduction {
+            Reduction::Modular => {
+                if let Some(idx) = monkeys.iter().position(|m| !m.operation.is_modular()) {
+                    panic!(
+                        "monkey {idx}'s operation \"{}\" can't be done modulo the test divisors",
+                        monkeys[idx].operation
+                    );
+                }
+                Some(
+                    monkeys
+                        .iter()
+                        .map(|m| m.test_divisor)
+                        .fold(1, num::integer::lcm),
+                )
             }
+            Reduction::Exact => None,
+        };
+        Self {
+            monkeys,
+            relief,
+            modulus,
         }
     }
+
+    /// Let each monkey in turn inspect and throw all its items, describing
+    /// each step in `narrative` as the puzzle does if one is given.
+    fn round(&mut self, mut narrative: Option<&mut String>) {
+        for idx in 0..self.monkeys.len() {
+            if let Some(ref mut out) = narrative {
+                writeln!(out, "Monkey {idx}:").unwrap();
+            }
+            while let Some(result) =
+                self.monkeys[idx].inspect_item(self.relief, self.modulus, narrative.as_deref_mut())
+            {
+                self.monkeys[result.destination]
+                    .items
+                    .push_back(result.item);
+            }
+        }
+    }
+
+    fn holdings(&self) -> String {
+        self.monkeys
+            .iter()
+            .enumerate()
+            .map(|(idx, m)| {
+                let items = m.items.iter().map(W::to_string).collect::<Vec<_>>();
+                format!("Monkey {idx}: {}\n", items.join(", "))
+            })
+            .collect()
+    }
+
+    fn inspections(&self) -> String {
+        self.monkeys
+            .iter()
+            .enumerate()
+            .map(|(idx, m)| format!("Monkey {idx} inspected items {} times.\n", m.inspections))
+            .collect()
+    }
+
+    fn monkey_business(&self) -> usize {
+        let mut inspections = self
+            .monkeys
+            .iter()
+            .map(|m| m.inspections)
+            .collect::<Vec<_>>();
+        inspections.sort_unstable();
+        inspections.iter().rev().take(2).product()
+    }
 }
 
-struct Monkey {
-    items: Vec<usize>,
-    operation: Box<dyn Fn(usize) -> usize>,
-    test: Box<dyn Fn(usize) -> usize>,
+struct Monkey<W> {
+    items: VecDeque<W>,
+    operation: Expr,
+    test_divisor: u64,
+    true_dest: usize,
+    false_dest: usize,
     inspections: usize,
-    test_divisor: usize,
 }
 
 #[derive(Debug, PartialEq)]
-struct OpResult {
-    item: usize,
+struct OpResult<W> {
+    item: W,
     destination: usize,
 }
 
-impl Monkey {
+impl<W: From<u64>> Monkey<W> {
     fn new(lines: &[&str; 5]) -> Self {
         let items = lines[0]
-            .split(": ")
-            .nth(1)
+            .split_once(':')
             .expect("couldn't tokenize items line")
-            .split(", ")
-            .map(|tok| tok.parse::<usize>().expect("non-integer item"))
-            .collect::<Vec<_>>();
-        let mut tokens = lines[1]
-            .split(" = old ")
-            .nth(1)
-            .expect("couldn't tokenize operation line")
-            .split(' ');
-        let op = tokens.next().expect("no operator found").to_owned();
-        let arg = tokens.next().expect("no argument found").to_owned();
-        let operation = move |old: usize| {
-            let arg = match arg.as_str() {
-                "old" => old,
-                n => n.parse::<usize>().expect("non-integer argument"),
-            };
-            match op.as_str() {
-                "+" => old + arg,
-                "-" => old - arg,
-                "*" => old * arg,
-                "/" => old / arg,
-                _ => panic!("unknown operator {op}"),
-            }
-        };
+            .1
+            .split(',')
+            .map(str::trim)
+            .filter(|tok| !tok.is_empty())
+            .map(|tok| W::from(tok.parse::<u64>().expect("non-integer item")))
+            .collect::<VecDeque<_>>();
+        let operation = Expr::parse(
+            lines[1]
+                .split(" = ")
+                .nth(1)
+                .expect("couldn't tokenize operation line"),
+        );
         let test_divisor = lines[2]
             .split("divisible by ")
             .nth(1)
             .expect("unable to parse divisor line")
-            .parse::<usize>()
+            .parse::<u64>()
             .expect("non-integer divisor");
         let true_dest = lines[3]
             .split("throw to monkey ")
@@ -125,40 +360,90 @@ impl Monkey {
             .expect("unable to parse false dest line")
             .parse::<usize>()
             .expect("non-integer destination");
-        let test = move |n| match n % test_divisor {
-            0 => true_dest,
-            _ => false_dest,
-        };
         Self {
             items,
-            operation: Box::new(operation),
-            test: Box::new(test),
-            inspections: 0,
+            operation,
             test_divisor,
+            true_dest,
+            false_dest,
+            inspections: 0,
         }
     }
+}
 
-    fn inspect_item(&mut self, divisor: usize, common_multiple: usize) -> Option<OpResult> {
-        if self.items.is_empty() {
-            None
-        } else {
-            self.inspections += 1;
-            let mut item = self.items.remove(0);
-            item = (self.operation)(item);
-            item /= divisor;
-            let num = item / common_multiple;
-            item -= num * common_multiple;
-            let destination = (self.test)(item);
-            Some(OpResult { item, destination })
+impl<W: Integer + Clone + fmt::Display + From<u64>> Monkey<W> {
+    fn destination(&self, item: &W) -> usize {
+        match item.is_multiple_of(&W::from(self.test_divisor)) {
+            true => self.true_dest,
+            false => self.false_dest,
         }
     }
+
+    fn inspect_item(
+        &mut self,
+        relief: u64,
+        modulus: Option<u64>,
+        narrative: Option<&mut String>,
+    ) -> Option<OpResult<W>> {
+        let old = self.items.pop_front()?;
+        self.inspections += 1;
+        let new = self.operation.eval(&old);
+        let relieved = new.clone() / W::from(relief);
+        let item = match modulus {
+            Some(m) => relieved.clone() % W::from(m),
+            None => relieved.clone(),
+        };
+        let destination = self.destination(&item);
+        if let Some(out) = narrative {
+            writeln!(
+                out,
+                "  Monkey inspects an item with a worry level of {old}."
+            )
+            .unwrap();
+            match self.operation.describe() {
+                Some(change) => writeln!(out, "    Worry level {change} to {new}.").unwrap(),
+                None => {
+                    writeln!(out, "    Worry level becomes {} = {new}.", self.operation).unwrap()
+                }
+            }
+            if relief != 1 {
+                writeln!(
+                    out,
+                    "    Monkey gets bored with item. Worry level is divided by {relief} to {relieved}."
+                )
+                .unwrap();
+            }
+            if item != relieved {
+                writeln!(
+                    out,
+                    "    Worry level is reduced modulo {} to {item}.",
+                    modulus.unwrap()
+                )
+                .unwrap();
+            }
+            let not = match item.is_multiple_of(&W::from(self.test_divisor)) {
+                true => "",
+                false => "not ",
+            };
+            writeln!(
+                out,
+                "    Current worry level is {not}divisible by {}.",
+                self.test_divisor
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "    Item with worry level {item} is thrown to monkey {destination}."
+            )
+            .unwrap();
+        }
+        Some(OpResult { item, destination })
+    }
 }
 
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod test {
-    use crate::Monkey;
-
     use super::*;
 
     static EXAMPLE: &str = r#"Monkey 0:
@@ -200,11 +485,11 @@ Monkey 3:
             .collect::<Vec<_>>()
             .try_into()
             .unwrap();
-        let monkey = Monkey::new(&lines);
-        assert_eq!(vec![79, 98], monkey.items);
-        assert_eq!(19, (monkey.operation)(1));
-        assert_eq!(2, (monkey.test)(23));
-        assert_eq!(3, (monkey.test)(24));
+        let monkey = Monkey::<u64>::new(&lines);
+        assert_eq!(VecDeque::from([79, 98]), monkey.items);
+        assert_eq!(19, monkey.operation.eval(&1u64));
+        assert_eq!(2, monkey.destination(&23));
+        assert_eq!(3, monkey.destination(&24));
     }
 
     #[test]
@@ -216,23 +501,169 @@ Monkey 3:
             .collect::<Vec<_>>()
             .try_into()
             .unwrap();
-        let mut monkey = Monkey::new(&lines);
+        let mut monkey = Monkey::<u64>::new(&lines);
         assert_eq!(
             OpResult {
                 item: 17,
                 destination: 3
             },
-            monkey.inspect_item(3, monkey.test_divisor).unwrap()
+            monkey
+                .inspect_item(3, Some(monkey.test_divisor), None)
+                .unwrap()
         );
     }
 
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_1_THEN_expected_answers_returned() {
-        assert_eq!(10605, part1(EXAMPLE));
+        assert_eq!(10605, part1(EXAMPLE, |_, _, _| {}));
     }
 
     #[test]
     fn GIVEN_aoc_example_WHEN_running_part_2_THEN_expected_answers_returned() {
         assert_eq!(2713310158, part2(EXAMPLE));
     }
+
+    #[test]
+    fn GIVEN_expressions_WHEN_parsing_THEN_precedence_and_order_respected() {
+        let cases = [
+            ("old * 19", 19 * 7, "old * 19"),
+            ("old * old", 7 * 7, "old * old"),
+            ("old + 2 * old", 7 + 2 * 7, "old + 2 * old"),
+            ("old * 2 + 1", 7 * 2 + 1, "old * 2 + 1"),
+            ("old - 3 - 2", 7 - 3 - 2, "old - 3 - 2"),
+            ("old / 2 * 3", 7 / 2 * 3, "old / 2 * 3"),
+            (
+                "100 - old * old / 7",
+                100 - 7 * 7 / 7,
+                "100 - old * old / 7",
+            ),
+        ];
+        for (text, value, display) in cases {
+            let expr = Expr::parse(text);
+            assert_eq!(value, expr.eval(&7u64), "{text}");
+            assert_eq!(display, expr.to_string());
+            assert_eq!(expr, Expr::parse(&expr.to_string()));
+        }
+        // brackets appear where the tree can't be written without them
+        let sum = Expr::Add(Box::new(Expr::Old), Box::new(Expr::Const(1)));
+        let expr = Expr::Sub(Box::new(Expr::Const(9)), Box::new(sum.clone()));
+        assert_eq!("9 - (old + 1)", expr.to_string());
+        let expr = Expr::Mul(Box::new(sum), Box::new(Expr::Const(2)));
+        assert_eq!("(old + 1) * 2", expr.to_string());
+        assert_eq!(
+            Some("is multiplied by itself".to_owned()),
+            Expr::parse("old * old").describe()
+        );
+        assert_eq!(None, Expr::parse("old * 2 + 1").describe());
+        assert!(Expr::parse("old * old + 2 * old").is_modular());
+        assert!(!Expr::parse("old * 2 - 1").is_modular());
+        assert!(!Expr::parse("old / 2 + 1").is_modular());
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be done modulo")]
+    fn GIVEN_subtracting_monkey_WHEN_reducing_modulo_divisors_THEN_panics() {
+        let monkeys = EXAMPLE.replace("new = old + 6", "new = old - 6");
+        Simulation::<u64>::new(parse(&monkeys), 3, Reduction::Modular);
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_narrating_first_round_THEN_matches_puzzle_text() {
+        let mut simulation = Simulation::<u64>::new(parse(EXAMPLE), 3, Reduction::Modular);
+        let mut narrative = String::new();
+        simulation.round(Some(&mut narrative));
+        let monkey_0 = "Monkey 0:
+  Monkey inspects an item with a worry level of 79.
+    Worry level is multiplied by 19 to 1501.
+    Monkey gets bored with item. Worry level is divided by 3 to 500.
+    Current worry level is not divisible by 23.
+    Item with worry level 500 is thrown to monkey 3.
+  Monkey inspects an item with a worry level of 98.
+    Worry level is multiplied by 19 to 1862.
+    Monkey gets bored with item. Worry level is divided by 3 to 620.
+    Current worry level is not divisible by 23.
+    Item with worry level 620 is thrown to monkey 3.
+Monkey 1:
+";
+        assert!(narrative.starts_with(monkey_0), "{narrative}");
+        assert!(narrative.contains(
+            "  Monkey inspects an item with a worry level of 79.
+    Worry level is multiplied by itself to 6241.
+    Monkey gets bored with item. Worry level is divided by 3 to 2080.
+    Current worry level is divisible by 13.
+    Item with worry level 2080 is thrown to monkey 1.
+"
+        ));
+        assert_eq!(
+            "Monkey 0: 20, 23, 27, 26
+Monkey 1: 2080, 25, 167, 207, 401, 1046
+Monkey 2: 
+Monkey 3: 
+",
+            simulation.holdings()
+        );
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_running_part_2_rounds_THEN_inspections_match_puzzle() {
+        let mut simulation = Simulation::<u64>::new(parse(EXAMPLE), 1, Reduction::Modular);
+        assert_eq!(Some(96577), simulation.modulus);
+        for _ in 0..20 {
+            simulation.round(None);
+        }
+        assert_eq!(
+            "Monkey 0 inspected items 99 times.
+Monkey 1 inspected items 97 times.
+Monkey 2 inspected items 8 times.
+Monkey 3 inspected items 103 times.
+",
+            simulation.inspections()
+        );
+    }
+
+    #[test]
+    fn GIVEN_relief_WHEN_reducing_worry_levels_THEN_exact_mode_shows_any_difference() {
+        assert_eq!(
+            10605,
+            run_simulation::<BigUint>(EXAMPLE, 20, 3, Reduction::Exact)
+        );
+        // monkey 0 triples 2 to 6 and the relief brings it back to 2, which
+        // the modular shortcut reduces to 0, so monkey 1 then doubles either
+        // 2 to 4 or 0 to 0, and after the relief the item is odd or even
+        let monkeys = "Monkey 0:
+  Starting items: 2
+  Operation: new = old * 3
+  Test: divisible by 2
+    If true: throw to monkey 1
+    If false: throw to monkey 2
+
+Monkey 1:
+  Starting items: 
+  Operation: new = old * 2
+  Test: divisible by 2
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 
+  Operation: new = old + 1
+  Test: divisible by 2
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+        let mut exact = Simulation::<BigUint>::new(parse(monkeys), 3, Reduction::Exact);
+        let mut modular = Simulation::<u64>::new(parse(monkeys), 3, Reduction::Modular);
+        exact.round(None);
+        modular.round(None);
+        assert_eq!("Monkey 0: 1\nMonkey 1: \nMonkey 2: \n", exact.holdings());
+        assert_eq!("Monkey 0: 0\nMonkey 1: \nMonkey 2: \n", modular.holdings());
+        assert_eq!(
+            380,
+            run_simulation::<BigUint>(monkeys, 20, 3, Reduction::Exact)
+        );
+        assert_eq!(
+            400,
+            run_simulation::<u64>(monkeys, 20, 3, Reduction::Modular)
+        );
+    }
 }
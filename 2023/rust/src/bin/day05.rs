@@ -7,6 +7,8 @@ use std::fs;
 
 use std::ops::Range;
 
+use aoc_common::interval::{IntervalSet, RangeMap};
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
@@ -21,54 +23,41 @@ fn main() {
 }
 
 fn part1(content: &str) -> i64 {
-    let mut lines = content.lines().filter(|s| !s.trim().is_empty());
-    let seeds = parse_seeds(
-        lines
-            .next()
-            .unwrap_or_else(|| panic!("no non-empty lines in file")),
-    );
-    let mut mapped = seeds.clone();
-    let mut mappings = Mappings(Vec::new());
-    let process_mapping = |mapped: &Vec<_>, mappings: &Mappings| {
-        mapped.iter().map(|&val| mappings.map(val)).collect()
-    };
-    for line in lines {
-        if line.contains("map:") {
-            mapped = process_mapping(&mapped, &mappings);
-            mappings.0.clear();
-        } else {
-            mappings.0.push(Mapping::new(line));
-        }
-    }
-    mapped = process_mapping(&mapped, &mappings);
-    *mapped.iter().min().expect("no seeds provided")
+    let (seeds_line, seed_to_location) = parse_almanac(content);
+    parse_seeds(seeds_line)
+        .into_iter()
+        .map(|seed| seed_to_location.map(seed))
+        .min()
+        .expect("no seeds provided")
 }
 
 fn part2(content: &str) -> i64 {
-    let mut lines = content.lines().filter(|s| !s.trim().is_empty());
-    let seeds = parse_seed_ranges(
-        lines
-            .next()
-            .unwrap_or_else(|| panic!("no non-empty lines in file")),
-    );
-    let lines = lines.collect::<Vec<_>>();
+    let (seeds_line, seed_to_location) = parse_almanac(content);
+    let seeds = IntervalSet::from_ranges(parse_seed_ranges(seeds_line));
+    seed_to_location
+        .min_image(&seeds)
+        .expect("no seeds provided")
+}
 
-    let mut mapped = seeds.clone();
-    let mut mappings = Mappings(Vec::new());
+/// Split the almanac into its seeds line and the composition of all of its
+/// maps, which sends a seed straight to its location.
+fn parse_almanac(content: &str) -> (&str, RangeMap<i64>) {
+    let mut lines = content.lines().filter(|s| !s.trim().is_empty());
+    let seeds_line = lines
+        .next()
+        .unwrap_or_else(|| panic!("no non-empty lines in file"));
+    let mut seed_to_location = RangeMap::default();
+    let mut mappings = Vec::new();
     for line in lines {
         if line.contains("map:") {
-            mapped = mappings.map_range(&mapped);
-            mappings.0.clear();
+            seed_to_location = seed_to_location.then(&range_map(&mappings));
+            mappings.clear();
         } else {
-            mappings.0.push(Mapping::new(line));
+            mappings.push(Mapping::new(line));
         }
     }
-    mapped = mappings.map_range(&mapped);
-    mapped
-        .iter()
-        .map(|r| r.start)
-        .min()
-        .expect("no seeds provided")
+    seed_to_location = seed_to_location.then(&range_map(&mappings));
+    (seeds_line, seed_to_location)
 }
 
 fn parse_seeds(line: &str) -> Vec<i64> {
@@ -92,7 +81,7 @@ fn parse_seed_ranges(line: &str) -> Vec<Range<i64>> {
     starts
         .iter()
         .zip(lengths.iter())
-        .map(|(&s, &l)| (s..(s + l)))
+        .map(|(&s, &l)| s..(s + l))
         .collect::<Vec<_>>()
 }
 
@@ -116,94 +105,18 @@ impl Mapping {
     }
 }
 
-fn intersection<T>(r1: &Range<T>, r2: &Range<T>) -> Option<Range<T>>
-where
-    T: Ord + Copy,
-{
-    let start = r1.start.max(r2.start);
-    let end = r1.end.min(r2.end);
-    if start <= end {
-        Some(start..end)
-    } else {
-        None
-    }
-}
-
-fn merge_ranges<T>(ranges: &[Range<T>]) -> Vec<Range<T>>
-where
-    T: Ord + Copy,
-{
-    let mut done = false;
-    let mut ranges = Vec::from(ranges);
-    while !done {
-        done = true;
-        let mut to_remove: Option<(usize, usize)> = None;
-        'l1: for (idx1, r1) in ranges.iter().enumerate() {
-            for (idx2, r2) in ranges.iter().enumerate().skip(idx1 + 1) {
-                if intersection(r1, r2).is_some() {
-                    to_remove = Some((idx1, idx2));
-                    done = false;
-                    break 'l1;
-                }
-            }
-        }
-        if let Some((idx1, idx2)) = to_remove {
-            let r1 = &ranges[idx1];
-            let r2 = &ranges[idx2];
-            let start = r1.start.min(r2.start);
-            let end = r1.end.max(r2.end);
-            ranges.push(start..end);
-            ranges.remove(idx2);
-            ranges.remove(idx1);
-        }
-    }
-    ranges.sort_by(|lhs, rhs| lhs.start.cmp(&rhs.start));
-    ranges
-}
-
-struct Mappings(Vec<Mapping>);
-
-impl Mappings {
-    fn map(&self, src: i64) -> i64 {
-        if let Some(mapping) = self.0.iter().find(|m| m.src.contains(&src)) {
-            src + mapping.offset
-        } else {
-            src
-        }
-    }
-    fn map_range(&self, srcs: &[Range<i64>]) -> Vec<Range<i64>> {
-        let mut result: Vec<Range<i64>> = Vec::new();
-        for src_range in srcs.iter() {
-            let mut src_mapped_ranges: Vec<Range<i64>> = Vec::new();
-            for mapping in self.0.iter() {
-                if let Some(r) = intersection(src_range, &mapping.src) {
-                    result.push((r.start + mapping.offset)..(r.end + mapping.offset));
-                    src_mapped_ranges.push(r);
-                }
-            }
-            // identity-map any unmapped src regions:
-            let mut src_mapped_ranges = merge_ranges(&src_mapped_ranges);
-            src_mapped_ranges.sort_by(|l, r| l.start.cmp(&r.start));
-            let mut last = src_range.start;
-            for r in src_mapped_ranges {
-                if r.start > last {
-                    result.push(last..r.start);
-                }
-                last = r.end;
-            }
-            if last < src_range.end {
-                result.push(last..src_range.end);
-            }
-        }
-        merge_ranges(&result)
-    }
+/// The piecewise-linear map for one block of mapping lines.
+fn range_map(mappings: &[Mapping]) -> RangeMap<i64> {
+    RangeMap::new(
+        mappings
+            .iter()
+            .map(|m| (m.src.clone(), m.src.start + m.offset)),
+    )
 }
 
 #[allow(non_snake_case)]
 #[cfg(test)]
 mod tester {
-    use std::collections::HashSet;
-
     use super::*;
 
     #[test]
@@ -223,7 +136,7 @@ mod tester {
     }
     #[test]
     fn GIVEN_mappings_WHEN_mapping_THEN_matches_expected() {
-        let mappings = Mappings(vec![Mapping::new("2 1 4"), Mapping::new("10 8 5")]);
+        let mappings = range_map(&[Mapping::new("2 1 4"), Mapping::new("10 8 5")]);
         assert_eq!(2, mappings.map(1));
         assert_eq!(3, mappings.map(2));
         assert_eq!(0, mappings.map(0));
@@ -244,65 +157,23 @@ mod tester {
         assert_eq!(parse_seed_ranges("seeds: 1 2 3 4"), vec![(1..3), (3..7)]);
     }
 
-    #[test]
-    fn GIVEN_several_ranges_WHEN_testing_intersections_THEN_correct_intersections_produced() {
-        let dotest = |r1, r2, expected: Option<Range<i32>>| {
-            let overlap = intersection(r1, r2);
-            if let Some(r3) = overlap {
-                assert!(expected.is_some());
-                assert_eq!(expected.unwrap(), r3);
-            } else {
-                assert!(expected.is_none());
-            }
-        };
-        dotest(&(-10..0), &(2..4), None); // no overlap not adjacent
-        dotest(&(-1..1), &(2..4), None); // no overlap adjacent
-        dotest(&(1..2), &(2..4), Some(2..2)); // overlap r1 < r2
-        dotest(&(1..3), &(-2..2), Some(1..2)); // overlap r2 < r1
-        dotest(&(11..23), &(15..18), Some(15..18)); // r2 contained by r1
-        dotest(&(11..23), &(1..50), Some(11..23)); // r1 contained by r2
-    }
-
-    #[test]
-    fn GIVEN_vector_of_ranges_WHEN_merging_THEN_merged_ranges_produced() {
-        let dotest = |ranges: Vec<Range<i32>>, expected: Vec<Range<i32>>| {
-            let min = ranges.iter().map(|r| r.start).min().unwrap();
-            let max = ranges.iter().map(|r| r.start).max().unwrap();
-            let merged_ranges = merge_ranges(&ranges);
-            // piecewise comparison across range:
-            for idx in (min - 5)..(max + 5) {
-                let orig_was_mapped = ranges.iter().any(|r| r.contains(&idx));
-                let merged_is_mapped = merged_ranges.iter().any(|r| r.contains(&idx));
-                assert_eq!(orig_was_mapped, merged_is_mapped);
-            }
-            assert_eq!(
-                HashSet::<Range<i32>>::from_iter(merged_ranges),
-                HashSet::from_iter(expected)
-            );
-        };
-        dotest(vec![2..4, 2..6, 9..12, 1..6], vec![1..6, 9..12]);
-    }
-
     #[test]
     #[allow(clippy::single_range_in_vec_init)]
     fn GIVEN_src_ranges_and_mappings_WHEN_mapping_src_ranges_THEN_matches_brutforce_map() {
-        let mappings = Mappings(Vec::<Mapping>::new());
-        let sanity_check = [0..20i64, 0..3i64];
-        let mapped = mappings.map_range(&sanity_check);
-        assert_eq!(HashSet::from([0..20i64]), HashSet::from_iter(mapped));
+        let mappings = range_map(&[]);
+        let sanity_check = IntervalSet::from_ranges([0..20i64, 0..3i64]);
+        let mapped = mappings.map_set(&sanity_check);
+        assert_eq!(mapped.ranges(), &[0..20i64]);
 
-        let mappings = Mappings(vec![Mapping::new("0 2 6"), Mapping::new("30 8 5")]);
+        let mappings = range_map(&[Mapping::new("0 2 6"), Mapping::new("30 8 5")]);
         let src_ranges = [0..20i64, 40..60];
-        let mapped = mappings.map_range(&src_ranges);
+        let mapped = mappings.map_set(&IntervalSet::from_ranges(src_ranges.clone()));
         let mut mapped_end_points = Vec::<i64>::new();
-        for range in mapped.iter() {
+        for range in mapped.ranges().iter() {
             mapped_end_points.extend(range.clone());
         }
         mapped_end_points.sort();
-        assert_eq!(
-            HashSet::from([0..6i64, 13..20i64, 30..35i64, 40..60i64]),
-            HashSet::from_iter(mapped)
-        );
+        assert_eq!(mapped.ranges(), &[0..6i64, 13..20i64, 30..35i64, 40..60i64]);
         // bruteforce:
         let start_points = src_ranges.into_iter().flatten().collect::<Vec<_>>();
         let mut end_points = start_points
@@ -350,6 +221,26 @@ humidity-to-location map:
 56 93 4
 "#;
 
+    #[test]
+    fn GIVEN_aoc_example_WHEN_composing_maps_THEN_seeds_map_to_their_locations() {
+        let (_, seed_to_location) = parse_almanac(EXAMPLE);
+        for (seed, location) in [(79, 82), (14, 43), (55, 86), (13, 35)] {
+            assert_eq!(location, seed_to_location.map(seed));
+        }
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_inverting_THEN_lowest_location_traced_to_seed() {
+        let (seeds_line, seed_to_location) = parse_almanac(EXAMPLE);
+        let seeds = IntervalSet::from_ranges(parse_seed_ranges(seeds_line));
+        let location_to_seed = seed_to_location
+            .inverse()
+            .expect("example maps are bijections");
+        assert_eq!(82, location_to_seed.map(46));
+        let at_46 = seed_to_location.preimage(&IntervalSet::from(46..47));
+        assert_eq!(IntervalSet::from(82..83), seeds.intersection(&at_46));
+    }
+
     #[test]
     fn GIVEN_aoc_example_WHEN_part1_run_THEN_matches_expected() {
         assert_eq!(35, part1(EXAMPLE));
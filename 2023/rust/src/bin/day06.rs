@@ -8,7 +8,7 @@
 ~/dev/advent_of_code/2023/rust/target/debug/day06 /home/stu/dev/advent_of_code/2023/input/day06.dat
 
 */
-use std::fs;
+use std::{fs, ops::RangeInclusive};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -23,39 +23,29 @@ fn main() {
     println!("part2 total is {}", part2(&contents));
 }
 
-fn quadratic_solver(a: f64, b: f64, c: f64) -> Option<(f64, f64)> {
-    let discriminant: f64 = b * b - (4.0 * a * c);
-    if discriminant >= 0.0 {
-        let sqrtd = discriminant.sqrt();
-        let scale = 1.0 / (2.0 * a);
-        let mut first = (-b + sqrtd) * scale;
-        let mut second = (-b - sqrtd) * scale;
-        if first > second {
-            (first, second) = (second, first);
-        }
-        Some((first, second))
-    } else {
-        None
+///
+/// The range of hold times `x` for which a boat in a race of length `time`
+/// travels further than `distance`, i.e. `x(time - x) > distance`. Completing
+/// the square gives `(2x - time)^2 < time^2 - 4 distance`, so the winners are
+/// the `x` where `2x - time` has the same parity as `time` and lies strictly
+/// inside the square root of the discriminant. This is evaluated with integer
+/// square roots so it stays exact however large the inputs.
+///
+fn winning_hold_times(time: u64, distance: u64) -> Option<RangeInclusive<u64>> {
+    let (time, distance) = (time as u128, distance as u128);
+    let discriminant = (time * time).checked_sub(4 * distance).filter(|&d| d > 0)?;
+    // largest offset whose square is strictly less than the discriminant
+    let mut offset = (discriminant - 1).isqrt();
+    if offset % 2 != time % 2 {
+        offset = offset.checked_sub(1)?;
     }
+    let first = (time - offset) / 2;
+    let last = (time + offset) / 2;
+    Some(first as u64..=last as u64)
 }
 
 fn discrete_number_above(time: u64, distance: u64) -> u64 {
-    // x(t-x) = d
-    let winner = |x| (x * (time - x)) > distance;
-    match quadratic_solver(-1., time as f64, distance as f64 * -1.0) {
-        Some((r1, r2)) => {
-            let mut u1 = r1.round() as u64;
-            let mut u2 = r2.round() as u64;
-            while !winner(u1) {
-                u1 += 1;
-            }
-            while winner(u2) {
-                u2 += 1;
-            }
-            u2 - u1
-        }
-        None => panic!("no roots found for time {time} distance {distance}"),
-    }
+    winning_hold_times(time, distance).map_or(0, |range| range.end() - range.start() + 1)
 }
 
 fn parse_line(line: &str) -> Vec<u64> {
@@ -110,21 +100,58 @@ fn part2(contents: &str) -> u64 {
 #[allow(non_snake_case)]
 mod tester {
     use super::*;
+    use aoc_common::test_util::XorShift;
 
     #[test]
-    fn GIVEN_quatratic_solver_WHEN_testing_known_inputs_THEN_expected_outputs_returned() {
-        let dotest = |a, b, c, expected| {
-            let result = quadratic_solver(a, b, c);
-            if let Some((r1, r2)) = result {
-                for r in [r1, r2] {
-                    assert_eq!(0.0, a * r * r + b * r + c); // TODO: fp comparison should be replaced with epsilon test
-                }
-            }
-            assert_eq!(expected, quadratic_solver(a, b, c));
+    fn GIVEN_time_and_distance_WHEN_finding_hold_times_THEN_exact_boundaries_returned() {
+        assert_eq!(Some(2..=5), winning_hold_times(7, 9));
+        assert_eq!(Some(11..=19), winning_hold_times(30, 200));
+        // perfect square discriminants, where the boundary times only tie
+        assert_eq!(Some(3..=7), winning_hold_times(10, 16)); // 2 * 8 = 16
+        assert_eq!(None, winning_hold_times(4, 4));
+        assert_eq!(Some(3..=3), winning_hold_times(6, 8)); // 2 * 4 = 8
+        assert_eq!(None, winning_hold_times(6, 9));
+        assert_eq!(None, winning_hold_times(5, 100));
+        assert_eq!(None, winning_hold_times(0, 0));
+    }
+
+    #[test]
+    fn GIVEN_random_races_WHEN_calc_winners_THEN_brute_force_count_matched() {
+        let brute_force = |time: u64, distance: u64| {
+            (0..=time).filter(|x| x * (time - x) > distance).count() as u64
         };
-        dotest(1., 0., 0., Some((0.0, 0.0)));
-        dotest(1., 0., 1., None);
-        dotest(-1., 3., -1.25, Some((0.5, 2.5)));
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        for _ in 0..2000 {
+            let time = rng.below(2000);
+            let best = (time / 2) * (time - time / 2);
+            let distance = match rng.below(4) {
+                // ties at the boundary: the distance reached by some hold time
+                0 => {
+                    let x = rng.below(time + 1);
+                    x * (time - x)
+                }
+                1 => best,
+                _ => rng.below(best + 10),
+            };
+            assert_eq!(
+                brute_force(time, distance),
+                discrete_number_above(time, distance),
+                "time {time} distance {distance}"
+            );
+        }
+    }
+
+    #[test]
+    fn GIVEN_huge_race_WHEN_calc_winners_THEN_result_is_exact() {
+        // x(t - x) > d with t = 2^32 + 1 and d chosen to tie at x = 2^31 - 5
+        let time = (1u64 << 32) + 1;
+        let x = (1u64 << 31) - 5;
+        let distance = x * (time - x);
+        assert_eq!(
+            Some(x + 1..=time - x - 1),
+            winning_hold_times(time, distance)
+        );
+        assert_eq!(Some(x..=time - x), winning_hold_times(time, distance - 1));
     }
 
     #[test]
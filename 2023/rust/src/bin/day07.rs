@@ -3,7 +3,7 @@
 //!
 //! See <https://adventofcode.com/2023/day/7>
 //!
-use std::{cmp::Ordering, fs};
+use std::{cmp::Ordering, fmt, fs, marker::PhantomData};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -19,24 +19,18 @@ fn main() {
 }
 
 fn part1(content: &str) -> usize {
-    let mut hands = content
-        .lines()
-        .filter(|l| !l.trim().is_empty())
-        .map(Hand::<Card>::parse)
-        .collect::<Vec<_>>();
-    hands.sort();
-    hands
-        .into_iter()
-        .enumerate()
-        .map(|(idx, hand)| (1 + idx) * hand.bid)
-        .sum()
+    total_winnings::<Standard>(content)
 }
 
 fn part2(content: &str) -> usize {
+    total_winnings::<Jokers>(content)
+}
+
+fn total_winnings<R: Rules>(content: &str) -> usize {
     let mut hands = content
         .lines()
         .filter(|l| !l.trim().is_empty())
-        .map(Hand::<CardWithJokers>::parse)
+        .map(Hand::<R>::parse)
         .collect::<Vec<_>>();
     hands.sort();
     hands
@@ -46,83 +40,7 @@ fn part2(content: &str) -> usize {
         .sum()
 }
 
-#[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Debug)]
-enum Card {
-    N2,
-    N3,
-    N4,
-    N5,
-    N6,
-    N7,
-    N8,
-    N9,
-    T,
-    J,
-    Q,
-    K,
-    A,
-}
-
-impl Card {
-    fn new(c: char) -> Self {
-        match c {
-            '2' => Card::N2,
-            '3' => Card::N3,
-            '4' => Card::N4,
-            '5' => Card::N5,
-            '6' => Card::N6,
-            '7' => Card::N7,
-            '8' => Card::N8,
-            '9' => Card::N9,
-            'T' => Card::T,
-            'J' => Card::J,
-            'Q' => Card::Q,
-            'K' => Card::K,
-            'A' => Card::A,
-            _ => panic!("unrecognised card {c}"),
-        }
-    }
-}
-
-#[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Debug)]
-enum CardWithJokers {
-    J,
-    N2,
-    N3,
-    N4,
-    N5,
-    N6,
-    N7,
-    N8,
-    N9,
-    T,
-    Q,
-    K,
-    A,
-}
-
-impl CardWithJokers {
-    fn new(c: char) -> Self {
-        match c {
-            '2' => CardWithJokers::N2,
-            '3' => CardWithJokers::N3,
-            '4' => CardWithJokers::N4,
-            '5' => CardWithJokers::N5,
-            '6' => CardWithJokers::N6,
-            '7' => CardWithJokers::N7,
-            '8' => CardWithJokers::N8,
-            '9' => CardWithJokers::N9,
-            'T' => CardWithJokers::T,
-            'J' => CardWithJokers::J,
-            'Q' => CardWithJokers::Q,
-            'K' => CardWithJokers::K,
-            'A' => CardWithJokers::A,
-            _ => panic!("unrecognised card {c}"),
-        }
-    }
-}
-
-#[derive(Ord, Eq, PartialOrd, PartialEq, Debug)]
+#[derive(Copy, Clone, Ord, Eq, PartialOrd, PartialEq, Debug)]
 enum HandType {
     HighCard,
     Pair,
@@ -134,171 +52,231 @@ enum HandType {
 }
 
 impl HandType {
-    fn new(mut cards: [Card; 5]) -> Self {
-        cards.sort();
-        let mut groups = Vec::<u8>::new();
-        let mut size = 1u8;
-        for (idx, card) in cards.into_iter().enumerate().skip(1) {
-            if card == cards[idx - 1] {
-                size += 1;
-            } else {
-                groups.push(size);
-                size = 1;
-            }
+    /// Classify a hand by its groups of identical cards, as both parts do.
+    fn classify<R: Rules + ?Sized>(cards: &[u8; 5]) -> Self {
+        let (groups, nwild) = group_cards::<R>(cards);
+        Self::from_groups(groups, nwild)
+    }
+
+    /// Classify a hand from the sizes of its groups of identical cards, with
+    /// any wildcards joining the largest group.
+    fn from_groups(mut groups: Vec<u8>, nwild: u8) -> Self {
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        match groups.first_mut() {
+            Some(largest) => *largest += nwild,
+            None => groups.push(nwild),
         }
-        groups.push(size);
-        groups.sort();
-        match groups {
-            _ if groups == vec![5] => Self::FiveOfAKind,
-            _ if groups == vec![1, 4] => Self::FourOfAKind,
-            _ if groups == vec![2, 3] => Self::FullHouse,
-            _ if groups == vec![1, 1, 3] => Self::ThreeOfAKind,
-            _ if groups == vec![1, 2, 2] => Self::TwoPair,
-            _ if groups == vec![1, 1, 1, 2] => Self::Pair,
-            _ if groups == vec![1, 1, 1, 1, 1] => Self::HighCard,
+        match groups[..] {
+            [5] => Self::FiveOfAKind,
+            [4, 1] => Self::FourOfAKind,
+            [3, 2] => Self::FullHouse,
+            [3, 1, 1] => Self::ThreeOfAKind,
+            [2, 2, 1] => Self::TwoPair,
+            [2, 1, 1, 1] => Self::Pair,
+            [1, 1, 1, 1, 1] => Self::HighCard,
             _ => panic!("unrecognized grouping {groups:?}"),
         }
     }
-    fn new_with_jokers(mut cards: [CardWithJokers; 5]) -> Self {
-        cards.sort();
-        let mut groups = Vec::<u8>::new();
-        let mut size = 1u8;
-        let mut last_card: Option<CardWithJokers> = None;
-        let mut njokers = 0u8;
-        for card in cards.into_iter() {
-            if card == CardWithJokers::J {
-                njokers += 1;
-            } else {
-                if let Some(c) = last_card {
-                    if card == c {
-                        size += 1;
-                    } else {
-                        groups.push(size);
-                        size = 1;
-                    }
-                }
-                last_card = Some(card);
-            }
-        }
-        if last_card.is_some() {
-            groups.push(size);
-            groups.sort();
-        }
-        match njokers {
-            5 | 4 => Self::FiveOfAKind,
-            3 => match groups.len() {
-                1 => Self::FiveOfAKind,
-                2 => Self::FourOfAKind,
-                _ => panic!("unexpected number of cards with 3 jokers!"),
-            },
-            2 => match groups.len() {
-                1 => Self::FiveOfAKind,
-                2 => Self::FourOfAKind,
-                3 => Self::ThreeOfAKind,
-                _ => panic!("unexpected number of cards with 2 jokers!"),
-            },
-            1 => match groups {
-                _ if groups == vec![4] => Self::FiveOfAKind,
-                _ if groups == vec![1, 3] => Self::FourOfAKind,
-                _ if groups == vec![2, 2] => Self::FullHouse,
-                _ if groups == vec![1, 1, 2] => Self::ThreeOfAKind,
-                _ if groups == vec![1, 1, 1, 1] => Self::Pair,
-                _ => panic!("unrecognized grouping of 4 cards {groups:?}"),
-            },
-            0 => match groups {
-                _ if groups == vec![5] => Self::FiveOfAKind,
-                _ if groups == vec![1, 4] => Self::FourOfAKind,
-                _ if groups == vec![2, 3] => Self::FullHouse,
-                _ if groups == vec![1, 1, 3] => Self::ThreeOfAKind,
-                _ if groups == vec![1, 2, 2] => Self::TwoPair,
-                _ if groups == vec![1, 1, 1, 2] => Self::Pair,
-                _ if groups == vec![1, 1, 1, 1, 1] => Self::HighCard,
-                _ => panic!("unrecognized grouping of 5 cards {groups:?}"),
-            },
-            _ => panic!("unexpected number of jokers - {njokers}"),
+}
+
+///
+/// The rules of a game variant: the order of card strengths, which cards are
+/// wild, and how a set of five cards is classified into a hand type.
+///
+trait Rules {
+    /// The types of hand, ordered from weakest to strongest.
+    type Kind: Copy + Ord + fmt::Debug;
+    /// Card labels in increasing order of strength.
+    const CARD_ORDER: &'static [u8];
+    /// Card labels which can stand in for any other card when classifying.
+    const WILDCARDS: &'static [u8] = b"";
+
+    fn is_valid(card: u8) -> bool {
+        Self::CARD_ORDER.contains(&card)
+    }
+
+    fn strength(card: u8) -> usize {
+        Self::CARD_ORDER
+            .iter()
+            .position(|&c| c == card)
+            .unwrap_or_else(|| panic!("unrecognised card {}", card as char))
+    }
+
+    fn is_wild(card: u8) -> bool {
+        Self::WILDCARDS.contains(&card)
+    }
+
+    fn classify(cards: &[u8; 5]) -> Self::Kind;
+}
+
+/// Part 1 rules: no wildcards, `J` is a jack.
+#[derive(Debug)]
+struct Standard;
+
+impl Rules for Standard {
+    type Kind = HandType;
+    const CARD_ORDER: &'static [u8] = b"23456789TJQKA";
+
+    fn classify(cards: &[u8; 5]) -> HandType {
+        HandType::classify::<Self>(cards)
+    }
+}
+
+/// Part 2 rules: `J` is a joker, wild but the weakest card for tie-breaks.
+#[derive(Debug)]
+struct Jokers;
+
+impl Rules for Jokers {
+    type Kind = HandType;
+    const CARD_ORDER: &'static [u8] = b"J23456789TQKA";
+    const WILDCARDS: &'static [u8] = b"J";
+
+    fn classify(cards: &[u8; 5]) -> HandType {
+        HandType::classify::<Self>(cards)
+    }
+}
+
+/// Sizes of the groups of identical non-wild cards, plus the number of wildcards.
+fn group_cards<R: Rules + ?Sized>(cards: &[u8; 5]) -> (Vec<u8>, u8) {
+    let mut sorted = *cards;
+    sorted.sort();
+    let mut groups = Vec::<u8>::new();
+    let mut last_card: Option<u8> = None;
+    let mut nwild = 0u8;
+    for card in sorted {
+        if R::is_wild(card) {
+            nwild += 1;
+        } else if last_card == Some(card) {
+            *groups.last_mut().unwrap() += 1;
+        } else {
+            groups.push(1);
+            last_card = Some(card);
         }
     }
+    (groups, nwild)
 }
 
-#[derive(PartialEq, Eq, Debug)]
-struct Hand<T> {
-    cards: [T; 5],
-    hand_type: HandType,
+#[derive(Debug)]
+struct Hand<R: Rules> {
+    cards: [u8; 5],
+    hand_type: R::Kind,
     bid: usize,
+    rules: PhantomData<R>,
 }
 
-impl Hand<Card> {
-    fn new(cards: [Card; 5], bid: usize) -> Self {
-        let hand_type = HandType::new(cards);
+impl<R: Rules> Hand<R> {
+    fn new(cards: [u8; 5], bid: usize) -> Self {
+        if let Some(&card) = cards.iter().find(|&&c| !R::is_valid(c)) {
+            panic!("unrecognised card {}", card as char);
+        }
+        let hand_type = R::classify(&cards);
         Self {
             cards,
             hand_type,
             bid,
+            rules: PhantomData,
         }
     }
+
     fn parse(line: &str) -> Self {
-        let (cards, bid) = parse_line(line, &Card::new);
+        let (cards, bid) = parse_line(line);
         Self::new(cards, bid)
     }
+
+    ///
+    /// Compare this hand with another, giving the reason for the ordering:
+    /// either the hand types differ, or the first differing card decides it.
+    ///
+    fn rank_explain(&self, other: &Self) -> RankExplanation<R::Kind> {
+        if self.hand_type != other.hand_type {
+            return RankExplanation::HandType(self.hand_type, other.hand_type);
+        }
+        for (idx, (&ours, &theirs)) in self.cards.iter().zip(other.cards.iter()).enumerate() {
+            if R::strength(ours) != R::strength(theirs) {
+                return RankExplanation::Card {
+                    position: idx,
+                    ours: ours as char,
+                    theirs: theirs as char,
+                    ordering: R::strength(ours).cmp(&R::strength(theirs)),
+                };
+            }
+        }
+        RankExplanation::Tie
+    }
 }
 
-impl Hand<CardWithJokers> {
-    fn new(cards: [CardWithJokers; 5], bid: usize) -> Self {
-        let hand_type = HandType::new_with_jokers(cards);
-        Self {
-            cards,
-            hand_type,
-            bid,
+#[derive(PartialEq, Eq, Debug)]
+enum RankExplanation<K> {
+    HandType(K, K),
+    Card {
+        position: usize,
+        ours: char,
+        theirs: char,
+        ordering: Ordering,
+    },
+    Tie,
+}
+
+impl<K: Ord> RankExplanation<K> {
+    fn ordering(&self) -> Ordering {
+        match self {
+            Self::HandType(ours, theirs) => ours.cmp(theirs),
+            Self::Card { ordering, .. } => *ordering,
+            Self::Tie => Ordering::Equal,
         }
     }
-    fn parse(line: &str) -> Self {
-        let (cards, bid) = parse_line(line, &CardWithJokers::new);
-        Self::new(cards, bid)
+}
+
+impl<K: Ord + fmt::Debug> fmt::Display for RankExplanation<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = match self.ordering() {
+            Ordering::Less => "loses to",
+            Ordering::Equal => "ties with",
+            Ordering::Greater => "beats",
+        };
+        match self {
+            Self::HandType(ours, theirs) => write!(f, "{ours:?} {verb} {theirs:?}"),
+            Self::Card {
+                position,
+                ours,
+                theirs,
+                ..
+            } => write!(
+                f,
+                "same hand type, card {} '{ours}' {verb} '{theirs}'",
+                position + 1
+            ),
+            Self::Tie => write!(f, "identical hand type and card strengths"),
+        }
     }
 }
 
-impl<T> Ord for Hand<T>
-where
-    T: Ord,
-{
+impl<R: Rules> Ord for Hand<R> {
     fn cmp(&self, other: &Self) -> Ordering {
-        let c = self.hand_type.cmp(&other.hand_type);
-        match c {
-            Ordering::Equal => {
-                for (idx, card) in self.cards.iter().enumerate() {
-                    let o = card.cmp(&other.cards[idx]);
-                    match o {
-                        Ordering::Equal => continue,
-                        _ => return o,
-                    }
-                }
-                Ordering::Equal
-            }
-            _ => c,
-        }
+        self.rank_explain(other).ordering()
+    }
+}
+
+impl<R: Rules> PartialEq for Hand<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
 }
 
-impl<T> PartialOrd for Hand<T>
-where
-    T: Ord,
-{
+impl<R: Rules> Eq for Hand<R> {}
+
+impl<R: Rules> PartialOrd for Hand<R> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-fn parse_line<T, F>(line: &str, factory: &F) -> ([T; 5], usize)
-where
-    F: Fn(char) -> T,
-{
+fn parse_line(line: &str) -> ([u8; 5], usize) {
     let mut iter = line.split(' ');
     let cards = iter
         .next()
         .unwrap()
-        .chars()
-        .map(factory)
-        .collect::<Vec<_>>()
+        .as_bytes()
         .try_into()
         .unwrap_or_else(|_| panic!("didn't find 5 cards in line {line}"));
     let bid = iter
@@ -314,28 +292,88 @@ where
 mod test07 {
     use super::*;
 
+    /// Jokers and deuces are both wild.
+    #[derive(Debug)]
+    struct JokersAndDeuces;
+
+    impl Rules for JokersAndDeuces {
+        type Kind = HandType;
+        const CARD_ORDER: &'static [u8] = b"J23456789TQKA";
+        const WILDCARDS: &'static [u8] = b"J2";
+
+        fn classify(cards: &[u8; 5]) -> HandType {
+            HandType::classify::<Self>(cards)
+        }
+    }
+
+    /// The hand types of part 1 with a straight ranked between three of a
+    /// kind and a full house.
+    #[derive(Copy, Clone, Ord, Eq, PartialOrd, PartialEq, Debug)]
+    enum StraightsHandType {
+        BelowStraight(HandType),
+        Straight,
+        AboveStraight(HandType),
+    }
+
+    /// Part 1 rules with five cards of consecutive strength also counting as
+    /// a straight.
+    #[derive(Debug)]
+    struct Straights;
+
+    impl Rules for Straights {
+        type Kind = StraightsHandType;
+        const CARD_ORDER: &'static [u8] = b"23456789TJQKA";
+
+        fn classify(cards: &[u8; 5]) -> StraightsHandType {
+            let (groups, nwild) = group_cards::<Self>(cards);
+            let is_straight = groups.iter().all(|&g| g == 1) && {
+                let strengths = cards
+                    .iter()
+                    .filter(|&&c| !Self::is_wild(c))
+                    .map(|&c| Self::strength(c));
+                match (strengths.clone().min(), strengths.max()) {
+                    (Some(lo), Some(hi)) => hi - lo < cards.len(),
+                    _ => true,
+                }
+            };
+            match HandType::from_groups(groups, nwild) {
+                by_groups if by_groups > HandType::ThreeOfAKind => {
+                    StraightsHandType::AboveStraight(by_groups)
+                }
+                _ if is_straight => StraightsHandType::Straight,
+                by_groups => StraightsHandType::BelowStraight(by_groups),
+            }
+        }
+    }
+
     #[test]
     fn GIVEN_pairs_of_cards_WHEN_comparing_THEN_correct_ordering_produced() {
-        assert!(Card::N2 < Card::N3);
-        assert!(Card::N2 == Card::N2);
-        assert!(Card::N3 > Card::N2);
-        assert!(Card::N9 < Card::T);
-        assert!(Card::K < Card::A);
+        let strength = Standard::strength;
+        assert!(strength(b'2') < strength(b'3'));
+        assert!(strength(b'2') == strength(b'2'));
+        assert!(strength(b'3') > strength(b'2'));
+        assert!(strength(b'9') < strength(b'T'));
+        assert!(strength(b'K') < strength(b'A'));
+        assert!(Jokers::strength(b'J') < Jokers::strength(b'2'));
+        assert!(Standard::is_valid(b'J') && Standard::is_valid(b'A'));
+        assert!(!Standard::is_valid(b'1') && !Standard::is_valid(b'j'));
+    }
+
+    #[test]
+    #[should_panic(expected = "unrecognised card X")]
+    fn GIVEN_unknown_card_WHEN_constructing_hand_THEN_panics() {
+        Hand::<Standard>::parse("AAXAA 1");
     }
 
     #[test]
     fn GIVEN_valid_line_WHEN_parsing_THEN_expected_output_produced() {
-        assert_eq!(
-            parse_line("32T3K 765", &Card::new),
-            ([Card::N3, Card::N2, Card::T, Card::N3, Card::K], 765)
-        );
+        assert_eq!(parse_line("32T3K 765"), (*b"32T3K", 765));
     }
 
     #[test]
     fn GIVEN_five_cards_WHEN_constructing_hand_THEN_correct_handtype_assigned() {
         let dotest = |line, expected| {
-            let (cards, score) = parse_line(line, &Card::new);
-            let hand = Hand::<Card>::new(cards, score);
+            let hand = Hand::<Standard>::parse(line);
             assert_eq!(hand.hand_type, expected);
         };
         dotest("AAAAA 1", HandType::FiveOfAKind);
@@ -350,8 +388,8 @@ mod test07 {
     #[test]
     fn GIVEN_several_hands_WHEN_ordering_THEN_correct_rules_followed() {
         let dotest = |hand1, hand2, expected_ordering| {
-            let hand1 = Hand::<Card>::parse(hand1);
-            let hand2 = Hand::<Card>::parse(hand2);
+            let hand1 = Hand::<Standard>::parse(hand1);
+            let hand2 = Hand::<Standard>::parse(hand2);
             assert_eq!(hand1.cmp(&hand2), expected_ordering);
         };
         dotest("AAAAA 1", "AAAAK 1", Ordering::Greater);
@@ -365,8 +403,7 @@ mod test07 {
     #[test]
     fn GIVEN_five_cards_with_jokers_WHEN_constructing_hand_THEN_correct_handtype_assigned() {
         let dotest = |line, expected| {
-            let (cards, score) = parse_line(line, &CardWithJokers::new);
-            let hand = Hand::<CardWithJokers>::new(cards, score);
+            let hand = Hand::<Jokers>::parse(line);
             assert_eq!(hand.hand_type, expected);
         };
         dotest("AAAJA 1", HandType::FiveOfAKind);
@@ -374,6 +411,62 @@ mod test07 {
         dotest("AQJKA 1", HandType::ThreeOfAKind);
         dotest("23JQK 1", HandType::Pair);
         dotest("7J543 1", HandType::Pair);
+        dotest("JJJJJ 1", HandType::FiveOfAKind);
+        dotest("KKJQQ 1", HandType::FullHouse);
+    }
+
+    #[test]
+    fn GIVEN_alternative_rules_WHEN_constructing_hand_THEN_correct_handtype_assigned() {
+        assert_eq!(
+            Hand::<JokersAndDeuces>::parse("2J3AK 1").hand_type,
+            HandType::ThreeOfAKind
+        );
+        assert_eq!(
+            Hand::<JokersAndDeuces>::parse("22JAA 1").hand_type,
+            HandType::FiveOfAKind
+        );
+        assert_eq!(
+            Hand::<Straights>::parse("T9QJK 1").hand_type,
+            StraightsHandType::Straight
+        );
+        assert_eq!(
+            Hand::<Straights>::parse("23456 1").hand_type,
+            StraightsHandType::Straight
+        );
+        assert_eq!(
+            Hand::<Straights>::parse("23457 1").hand_type,
+            StraightsHandType::BelowStraight(HandType::HighCard)
+        );
+        assert_eq!(
+            Hand::<Straights>::parse("23455 1").hand_type,
+            StraightsHandType::BelowStraight(HandType::Pair)
+        );
+        assert!(Hand::<Straights>::parse("AKQJT 1") < Hand::<Straights>::parse("22333 1"));
+        assert!(Hand::<Straights>::parse("23456 1") > Hand::<Straights>::parse("AAAKQ 1"));
+    }
+
+    #[test]
+    fn GIVEN_two_hands_WHEN_explaining_rank_THEN_deciding_reason_reported() {
+        let explain =
+            |hand1, hand2| Hand::<Jokers>::parse(hand1).rank_explain(&Hand::<Jokers>::parse(hand2));
+        assert_eq!(
+            explain("QQQJA 1", "T55J5 1"),
+            RankExplanation::Card {
+                position: 0,
+                ours: 'Q',
+                theirs: 'T',
+                ordering: Ordering::Greater
+            }
+        );
+        assert_eq!(
+            explain("KK677 1", "KTJJT 1"),
+            RankExplanation::HandType(HandType::TwoPair, HandType::FourOfAKind)
+        );
+        assert_eq!(
+            explain("JKKK2 1", "QQQQ2 1").to_string(),
+            "same hand type, card 1 'J' loses to 'Q'"
+        );
+        assert_eq!(explain("KTJJT 1", "KTJJT 1"), RankExplanation::Tie);
     }
 
     static EXAMPLE_INPUT: &str = r#"
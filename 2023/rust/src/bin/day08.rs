@@ -3,8 +3,9 @@
 //!
 //! See <https://adventofcode.com/2023/day/8>
 //!
+use num::Integer;
 use std::collections::HashMap;
-use std::fs;
+use std::{fmt, fs};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -16,7 +17,10 @@ fn main() {
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
 
     println!("part 1 answer is {}", part1(contents.as_str()));
-    println!("part 2 answer is {}", part2(&contents));
+    match part2(&contents) {
+        Ok(answer) => println!("part 2 answer is {answer}"),
+        Err(err) => println!("part 2 has no answer: {err}"),
+    }
 }
 
 type NodeName = [char; 3];
@@ -29,64 +33,206 @@ fn part1(contents: &str) -> usize {
     })
 }
 
-fn part2(contents: &str) -> usize {
+fn part2(contents: &str) -> Result<usize, WalkError> {
     let (map, directions) = parse_file(contents);
     let start_nodes = map
         .keys()
         .filter_map(|n| if 'A' == n[2] { Some(*n) } else { None })
         .collect::<Vec<_>>();
-    walk_map_simultaneously(map, directions, start_nodes, |node: &NodeName| {
+    walk_map_simultaneously(&map, &directions, &start_nodes, |node: &NodeName| {
         'Z' == node[2]
     })
 }
 
+fn next_node<'a>(map: &'a Map, node: &NodeName, left: bool) -> &'a NodeName {
+    let entry = map.get(node).unwrap_or_else(|| {
+        panic!(
+            "unable to find network node for \"{}\"",
+            String::from_iter(node)
+        )
+    });
+    match left {
+        true => &entry.0,
+        false => &entry.1,
+    }
+}
+
 fn walk_map(
     map: &Map,
-    directions: &Vec<bool>,
+    directions: &[bool],
     start: &NodeName,
     predicate: impl Fn(&[char; 3]) -> bool,
 ) -> usize {
     let mut next = start;
     let mut nsteps = 0;
     while !predicate(next) {
-        let entry = map.get(next).unwrap_or_else(|| {
-            panic!(
-                "unable to find network node for \"{}\"",
-                String::from_iter(next)
-            )
-        });
-        next = match directions[nsteps % directions.len()] {
-            true => &entry.0,
-            false => &entry.1,
-        };
+        next = next_node(map, next, directions[nsteps % directions.len()]);
         nsteps += 1
     }
     nsteps
 }
 
+///
+/// The shape of a single walk. The state of a walker is its node together with
+/// its position in the directions list, so after `tail` steps the walk must
+/// enter a loop of `cycle_len` steps. End nodes are reached at the (absolute)
+/// steps listed in `tail_hits` before the loop is entered, and at the steps in
+/// `cycle_hits` during the first pass around it.
+///
+#[derive(Debug, PartialEq)]
+struct WalkCycle {
+    tail: usize,
+    cycle_len: usize,
+    tail_hits: Vec<usize>,
+    cycle_hits: Vec<usize>,
+}
+
+impl WalkCycle {
+    fn is_hit(&self, step: usize) -> bool {
+        if step < self.tail {
+            self.tail_hits.contains(&step)
+        } else {
+            let step = self.tail + (step - self.tail) % self.cycle_len;
+            self.cycle_hits.contains(&step)
+        }
+    }
+}
+
+fn analyse_walk(
+    map: &Map,
+    directions: &[bool],
+    start: &NodeName,
+    predicate: impl Fn(&[char; 3]) -> bool,
+) -> WalkCycle {
+    let mut seen = HashMap::<(NodeName, usize), usize>::new();
+    let mut hits = Vec::new();
+    let mut node = start;
+    let mut step = 0;
+    loop {
+        let idx = step % directions.len();
+        if let Some(&first) = seen.get(&(*node, idx)) {
+            let (tail_hits, cycle_hits) = hits.into_iter().partition(|&hit| hit < first);
+            return WalkCycle {
+                tail: first,
+                cycle_len: step - first,
+                tail_hits,
+                cycle_hits,
+            };
+        }
+        seen.insert((*node, idx), step);
+        if predicate(node) {
+            hits.push(step);
+        }
+        node = next_node(map, node, directions[idx]);
+        step += 1;
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum WalkError {
+    NoStartNodes,
+    NeverFinishes(NodeName),
+    NoCommonStep(Vec<NodeName>),
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoStartNodes => write!(f, "no start nodes in map"),
+            Self::NeverFinishes(node) => write!(
+                f,
+                "the walk from {} never reaches an end node",
+                String::from_iter(node)
+            ),
+            Self::NoCommonStep(nodes) => write!(
+                f,
+                "the walks from {} never reach end nodes on the same step",
+                nodes
+                    .iter()
+                    .map(String::from_iter)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
 fn walk_map_simultaneously(
-    route_map: Map,
-    directions: Vec<bool>,
-    start: Vec<NodeName>,
+    route_map: &Map,
+    directions: &[bool],
+    start: &[NodeName],
     predicate: impl Fn(&[char; 3]) -> bool,
-) -> usize {
-    // The AoC data is arranged so that each lane loops back over itself,
-    // therefore the answer is the lowest common multiple of the individual lane
-    // loop counts. This doesn't seem to be a general solution to the problem,
-    // but the brute-force parallel search would take days to run.
-    let mut common_multiple: Option<usize> = None;
-    start.iter().for_each(|key| {
-        let nsteps = walk_map(&route_map, &directions, key, &predicate);
-        common_multiple = match common_multiple {
-            Some(n) => Some(num::integer::lcm(n, nsteps)),
-            None => Some(nsteps),
+) -> Result<usize, WalkError> {
+    // Brute-force parallel search would take days to run, so analyse each walk
+    // separately and then combine the step numbers at which they finish.
+    let walks = start
+        .iter()
+        .map(|node| analyse_walk(route_map, directions, node, &predicate))
+        .collect::<Vec<_>>();
+    if let Some((node, _)) = start
+        .iter()
+        .zip(walks.iter())
+        .find(|(_, walk)| walk.tail_hits.is_empty() && walk.cycle_hits.is_empty())
+    {
+        return Err(WalkError::NeverFinishes(*node));
+    }
+
+    // Until every walk has entered its loop, the only candidates are the tail
+    // hits of the walk with the longest tail.
+    let longest = walks
+        .iter()
+        .max_by_key(|walk| walk.tail)
+        .ok_or(WalkError::NoStartNodes)?;
+    if let Some(&step) = longest
+        .tail_hits
+        .iter()
+        .find(|&&step| walks.iter().all(|walk| walk.is_hit(step)))
+    {
+        return Ok(step);
+    }
+
+    // After that each walk finishes on steps satisfying any one of a set of
+    // congruences, which are combined pairwise with the Chinese Remainder
+    // Theorem.
+    let mut congruences = vec![(0i128, 1i128)];
+    for (idx, walk) in walks.iter().enumerate() {
+        let cycle_len = walk.cycle_len as i128;
+        congruences = congruences
+            .iter()
+            .flat_map(|&(r, m)| {
+                walk.cycle_hits
+                    .iter()
+                    .filter_map(move |&hit| crt(r, m, hit as i128, cycle_len))
+            })
+            .collect();
+        congruences.sort();
+        congruences.dedup();
+        if congruences.is_empty() {
+            return Err(WalkError::NoCommonStep(start[..=idx].to_vec()));
         }
-    });
-    if let Some(n) = common_multiple {
-        n
-    } else {
-        panic!("No routes in map!");
     }
+    let min_step = longest.tail as i128;
+    let step = congruences
+        .into_iter()
+        .map(|(r, m)| r + ((min_step - r).max(0) + m - 1) / m * m)
+        .min()
+        .unwrap();
+    Ok(step as usize)
+}
+
+///
+/// Combine the congruences `x = r1 (mod m1)` and `x = r2 (mod m2)`, where the
+/// moduli need not be coprime. Returns the combined residue and modulus, or
+/// `None` if no `x` satisfies both.
+///
+fn crt(r1: i128, m1: i128, r2: i128, m2: i128) -> Option<(i128, i128)> {
+    let egcd = m1.extended_gcd(&m2);
+    if (r2 - r1) % egcd.gcd != 0 {
+        return None;
+    }
+    let lcm = m1 / egcd.gcd * m2;
+    let t = ((r2 - r1) / egcd.gcd * egcd.x).rem_euclid(m2 / egcd.gcd);
+    Some(((r1 + m1 * t).rem_euclid(lcm), lcm))
 }
 
 fn parse_file(contents: &str) -> (Map, Vec<bool>) {
@@ -155,6 +301,100 @@ XXX = (XXX, XXX)
 
     #[test]
     fn GIVEN_aoc_example_input_WHEN_part2_run_THEN_expected_total_returned() {
-        assert_eq!(6, part2(EXAMPLE_2));
+        assert_eq!(Ok(6), part2(EXAMPLE_2));
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_input_WHEN_analysing_walks_THEN_tail_and_cycle_found() {
+        let (map, directions) = parse_file(EXAMPLE_2);
+        let is_end = |node: &NodeName| 'Z' == node[2];
+        assert_eq!(
+            analyse_walk(&map, &directions, &['1', '1', 'A'], is_end),
+            WalkCycle {
+                tail: 1,
+                cycle_len: 2,
+                tail_hits: vec![],
+                cycle_hits: vec![2]
+            }
+        );
+        assert_eq!(
+            analyse_walk(&map, &directions, &['2', '2', 'A'], is_end),
+            WalkCycle {
+                tail: 1,
+                cycle_len: 6,
+                tail_hits: vec![],
+                cycle_hits: vec![3, 6]
+            }
+        );
+    }
+
+    #[test]
+    fn GIVEN_congruences_WHEN_combining_THEN_generalised_crt_applied() {
+        assert_eq!(Some((7, 12)), crt(3, 4, 1, 3));
+        assert_eq!(Some((2, 12)), crt(2, 4, 8, 6));
+        assert_eq!(None, crt(0, 4, 1, 6));
+        assert_eq!(Some((5, 7)), crt(0, 1, 5, 7));
+    }
+
+    // The first end node is not reached at a multiple of the loop length, so
+    // the lowest common multiple of the first hits (3) would be wrong.
+    static OFFSET_LOOPS: &str = r#"
+L
+
+11A = (11B, XXX)
+11B = (11C, XXX)
+11C = (11Z, XXX)
+11Z = (11D, XXX)
+11D = (11B, XXX)
+22A = (22Z, XXX)
+22Z = (22B, XXX)
+22B = (22C, XXX)
+22C = (22Z, XXX)
+XXX = (XXX, XXX)
+"#;
+
+    #[test]
+    fn GIVEN_loops_with_offset_end_nodes_WHEN_part2_run_THEN_crt_solution_returned() {
+        assert_eq!(Ok(7), part2(OFFSET_LOOPS));
+    }
+
+    static ONLY_IN_TAIL: &str = r#"
+L
+
+11A = (11Z, XXX)
+11Z = (11B, XXX)
+11B = (11B, XXX)
+22A = (22Z, XXX)
+22Z = (22Z, XXX)
+XXX = (XXX, XXX)
+"#;
+
+    #[test]
+    fn GIVEN_end_node_only_before_loop_WHEN_part2_run_THEN_tail_solution_returned() {
+        assert_eq!(Ok(1), part2(ONLY_IN_TAIL));
+    }
+
+    static NEVER_TOGETHER: &str = r#"
+L
+
+11A = (11Z, XXX)
+11Z = (11A, XXX)
+22A = (22B, XXX)
+22B = (22Z, XXX)
+22Z = (22B, XXX)
+XXX = (XXX, XXX)
+"#;
+
+    #[test]
+    fn GIVEN_incompatible_loops_WHEN_part2_run_THEN_error_returned() {
+        let err = part2(NEVER_TOGETHER).unwrap_err();
+        assert!(matches!(err, WalkError::NoCommonStep(_)));
+        assert!(err
+            .to_string()
+            .contains("never reach end nodes on the same step"));
+        assert_eq!(
+            Err(WalkError::NeverFinishes(['X', 'X', 'A'])),
+            part2("L\n\nXXA = (XXX, XXX)\nXXX = (XXX, XXX)\n")
+        );
     }
 }
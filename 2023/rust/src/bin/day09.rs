@@ -3,6 +3,7 @@
 //!
 //! See <https://adventofcode.com/2023/day/9>
 //!
+use num::{BigInt, BigRational, One, ToPrimitive, Zero};
 use std::fs;
 
 fn main() {
@@ -16,29 +17,95 @@ fn main() {
 
     println!("part1 answer is {}", part1(contents.as_str()));
     println!("part2 answer is {}", part2(contents.as_str()));
+    println!(
+        "the highest degree of any sequence is {}",
+        max_degree(contents.as_str())
+    );
 }
 
 fn part1(contents: &str) -> i64 {
     let lines = parse_file(contents);
-    lines.iter().map(|l| predict(l, true)).sum()
+    lines.iter().map(|l| extrapolate(l, l.len() as i64)).sum()
 }
 
 fn part2(contents: &str) -> i64 {
     let lines = parse_file(contents);
-    lines.iter().map(|l| predict(l, false)).sum()
+    lines.iter().map(|l| extrapolate(l, -1)).sum()
 }
 
-fn predict(points: &[i64], forwards: bool) -> i64 {
-    if points.iter().all(|n| 0 == *n) {
-        0
-    } else {
-        let derivative = differentiate(points);
-        let delta = predict(&derivative, forwards);
-        if forwards {
-            points[points.len() - 1] + delta
-        } else {
-            points[0] - delta
+fn max_degree(contents: &str) -> usize {
+    let lines = parse_file(contents);
+    lines
+        .iter()
+        .map(|l| match Polynomial::fit(l) {
+            Ok(poly) => poly.degree(),
+            Err(err) => panic!("can't fit {l:?}: {err:?}"),
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn extrapolate(points: &[i64], index: i64) -> i64 {
+    let poly = Polynomial::fit(points)
+        .unwrap_or_else(|err| panic!("can't extrapolate {points:?}: {err:?}"));
+    poly.evaluate(index)
+        .to_integer()
+        .to_i64()
+        .expect("extrapolated value out of range")
+}
+
+#[derive(Debug, PartialEq)]
+enum FitError {
+    NoPoints,
+    /// The difference table ran out of rows before reaching one of all zeros,
+    /// so the points don't pin down a polynomial. Holds the final non-zero row.
+    NoZeroRow(Vec<i64>),
+}
+
+///
+/// The polynomial through a sequence of points at indices 0, 1, 2..., held in
+/// Newton form: `p(x) = c0 + c1 x + c2 x(x-1) + c3 x(x-1)(x-2) + ...`, where
+/// `ck` is the first entry of the kth row of the difference table divided by
+/// k factorial.
+///
+#[derive(Debug)]
+struct Polynomial {
+    coefficients: Vec<BigRational>,
+}
+
+impl Polynomial {
+    fn fit(points: &[i64]) -> Result<Self, FitError> {
+        if points.is_empty() {
+            return Err(FitError::NoPoints);
+        }
+        let mut coefficients = Vec::new();
+        let mut factorial = BigInt::one();
+        let mut row = points.to_vec();
+        while !row.iter().all(|n| 0 == *n) {
+            if row.len() == 1 {
+                return Err(FitError::NoZeroRow(row));
+            }
+            coefficients.push(BigRational::new(row[0].into(), factorial.clone()));
+            factorial *= coefficients.len();
+            row = differentiate(&row);
         }
+        Ok(Self { coefficients })
+    }
+
+    /// The degree of the polynomial, where the zero polynomial has degree 0.
+    fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    fn evaluate(&self, x: i64) -> BigRational {
+        let x = BigRational::from_integer(x.into());
+        self.coefficients
+            .iter()
+            .enumerate()
+            .rev()
+            .fold(BigRational::zero(), |acc, (k, coeff)| {
+                acc * (&x - BigRational::from_integer(k.into())) + coeff
+            })
     }
 }
 
@@ -82,6 +149,52 @@ mod test09 {
         dotest(&[2, 2, 2, 2], vec![0, 0, 0]);
     }
 
+    #[test]
+    fn GIVEN_polynomial_points_WHEN_fitting_THEN_degree_detected() {
+        let degree = |points: &[i64]| Polynomial::fit(points).unwrap().degree();
+        assert_eq!(0, degree(&[0, 0, 0]));
+        assert_eq!(0, degree(&[5, 5, 5]));
+        assert_eq!(1, degree(&[0, 3, 6, 9, 12, 15]));
+        assert_eq!(2, degree(&[9, 16, 25, 36, 49, 64]));
+        assert_eq!(3, degree(&[0, 1, 8, 27, 64, 125, 216]));
+    }
+
+    #[test]
+    fn GIVEN_polynomial_points_WHEN_evaluating_THEN_exact_values_at_any_index() {
+        // y = (x + 3)^2
+        let poly = Polynomial::fit(&[9, 16, 25, 36, 49, 64]).unwrap();
+        let at = |x: i64| poly.evaluate(x).to_integer();
+        assert_eq!(BigInt::from(81), at(6));
+        assert_eq!(BigInt::from(4), at(-1));
+        assert_eq!(BigInt::from(0), at(-3));
+        assert_eq!(BigInt::from(9409), at(-100));
+        assert_eq!(
+            "1000000000000000006000000000000000009"
+                .parse::<BigInt>()
+                .unwrap(),
+            at(1_000_000_000_000_000_000)
+        );
+        // y = x(x-1)(x-2) / 6 has fractional coefficients but whole values at
+        // whole x
+        let poly = Polynomial::fit(&[0, 0, 0, 1, 4, 10, 20]).unwrap();
+        assert_eq!(3, poly.degree());
+        assert_eq!(BigInt::from(35), poly.evaluate(7).to_integer());
+        assert_eq!(BigInt::from(-1), poly.evaluate(-1).to_integer());
+    }
+
+    #[test]
+    fn GIVEN_non_polynomial_points_WHEN_fitting_THEN_error_returned() {
+        assert_eq!(Some(FitError::NoPoints), Polynomial::fit(&[]).err());
+        assert_eq!(
+            Some(FitError::NoZeroRow(vec![1])),
+            Polynomial::fit(&[1, 2, 4]).err()
+        );
+        assert_eq!(
+            Some(FitError::NoZeroRow(vec![7])),
+            Polynomial::fit(&[7]).err()
+        );
+    }
+
     static EXAMPLE_INPUT: &str = r#"
 0 3 6 9 12 15
 1 3 6 10 15 21
@@ -97,4 +210,9 @@ mod test09 {
     fn GIVEN_aoc_example_WHEN_part2_run_THEN_matches_expected() {
         assert_eq!(2, part2(EXAMPLE_INPUT));
     }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_finding_max_degree_THEN_cubic_found() {
+        assert_eq!(3, max_degree(EXAMPLE_INPUT));
+    }
 }
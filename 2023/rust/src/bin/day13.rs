@@ -3,7 +3,7 @@
 //!
 //! See <https://adventofcode.com/2023/day/13>
 //!
-use std::{cell::Cell, fs};
+use std::fs;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -19,29 +19,23 @@ fn main() {
 }
 
 type Group = Vec<Vec<char>>;
-type MutGroup = Vec<Vec<Cell<char>>>;
 
 fn part1(contents: &str) -> usize {
-    let groups = get_groups(contents);
-    groups.into_iter().map(process_group).sum()
+    total_score(contents, 0)
 }
 
 fn part2(contents: &str) -> usize {
-    let to_mut_group = |group: Group| {
-        group
-            .into_iter()
-            .map(|line| line.iter().map(|&c| Cell::new(c)).collect::<Vec<_>>())
-            .collect::<Vec<_>>()
-    };
-    let groups = get_groups(contents);
-    let groups = groups.into_iter().map(to_mut_group).collect::<Vec<_>>();
+    total_score(contents, 1)
+}
 
-    groups
-        .into_iter()
+fn total_score(contents: &str, smudges: u32) -> usize {
+    get_groups(contents)
+        .iter()
+        .map(Pattern::new)
         .enumerate()
-        .map(|(idx, g)| {
-            process_smudged_group(g)
-                .unwrap_or_else(|| panic!("unable to find reflection for {idx}"))
+        .map(|(idx, pattern)| match pattern.reflections(smudges)[..] {
+            [axis] => axis.score(),
+            ref axes => panic!("expected one reflection for {idx}, found {axes:?}"),
         })
         .sum()
 }
@@ -66,104 +60,106 @@ fn get_groups(contents: &str) -> Vec<Group> {
     groups
 }
 
-fn transpose<T>(group: &[Vec<T>]) -> Vec<Vec<T>>
-where
-    T: Clone,
-{
-    let mut vlines = Vec::<Vec<T>>::new();
-    for (i, line) in group.iter().enumerate() {
-        line.iter().enumerate().for_each(|(j, c)| {
-            if 0 == i {
-                vlines.push(vec![c.clone()]);
-            } else {
-                vlines[j].push(c.clone());
-            }
-        });
-    }
-    vlines
+/// A line of reflection, lying after the given number of rows or columns.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Axis {
+    Horizontal(usize),
+    Vertical(usize),
 }
 
-fn test_symmetry<T>(lines: &[T], ignore: Option<usize>) -> Option<usize>
-where
-    T: PartialOrd,
-{
-    'l1: for (idx, line) in lines.iter().enumerate().skip(1) {
-        if *line == lines[idx - 1] {
-            let num = (lines.len() - idx - 1).min(idx - 1);
-            for i in 0..num {
-                let l1 = &lines[idx + i + 1];
-                let ls = &lines[idx - i - 2];
-                if l1 != ls {
-                    continue 'l1;
-                }
-            }
-            if let Some(score) = ignore {
-                if idx == score {
-                    continue 'l1;
-                }
-            }
-            return Some(idx);
+impl Axis {
+    fn score(&self) -> usize {
+        match self {
+            Self::Horizontal(idx) => idx * 100,
+            Self::Vertical(idx) => *idx,
         }
     }
-    None
 }
 
-fn process_group(horizontal_lines: Group) -> usize {
-    if let Some(idx) = test_symmetry(&horizontal_lines, None) {
-        return idx * 100;
-    }
-    let vertical_lines = transpose(&horizontal_lines);
-    if let Some(idx) = test_symmetry(&vertical_lines, None) {
-        return idx;
-    }
-    panic!("no reflections found");
+///
+/// A pattern of ash and rocks, with each row and each column encoded as a
+/// bitmask of its rocks. The number of cells which differ between a line and
+/// its mirror image is then the popcount of their XOR.
+///
+#[derive(Debug)]
+struct Pattern {
+    rows: Vec<u64>,
+    cols: Vec<u64>,
 }
 
-fn process_smudged_group(horizontal_lines: MutGroup) -> Option<usize> {
-    let vertical_lines = transpose(&horizontal_lines);
-    let mut original_score = test_symmetry(&horizontal_lines, None);
-    if let Some(score) = original_score {
-        original_score = Some(score * 100);
-    }
-    if original_score.is_none() {
-        original_score = test_symmetry(&vertical_lines, None);
-    }
-    let original_score = original_score
-        .unwrap_or_else(|| panic!("unable to calc original score for {:?}", &horizontal_lines));
-
-    let flip = |c: &Cell<char>| {
-        let new_c = if c.get() == '#' { '.' } else { '#' };
-        c.replace(new_c);
-    };
-    let to_ignore = match original_score {
-        _ if original_score >= 100 => Some(original_score / 100),
-        _ => None,
-    };
-    for line in horizontal_lines.iter() {
-        for c in line.iter() {
-            flip(c);
-            let result = test_symmetry(&horizontal_lines, to_ignore);
-            flip(c);
-            if let Some(score) = result {
-                return Some(score * 100);
+impl Pattern {
+    fn new(group: &Group) -> Self {
+        let width = group.first().map_or(0, |line| line.len());
+        assert!(
+            width <= 64 && group.len() <= 64,
+            "pattern too large for bitmasks"
+        );
+        let mut rows = vec![0u64; group.len()];
+        let mut cols = vec![0u64; width];
+        for (i, line) in group.iter().enumerate() {
+            for (j, &c) in line.iter().enumerate() {
+                if c == '#' {
+                    rows[i] |= 1 << j;
+                    cols[j] |= 1 << i;
+                }
             }
         }
+        Self { rows, cols }
     }
-    let to_ignore = match original_score {
-        _ if original_score < 100 => Some(original_score),
-        _ => None,
-    };
-    for line in vertical_lines.iter() {
-        for c in line.iter() {
-            flip(c);
-            let result = test_symmetry(&vertical_lines, to_ignore);
-            flip(c);
-            if let Some(score) = result {
-                return Some(score);
+
+    /// Every axis which would be a perfect reflection after fixing exactly
+    /// `smudges` cells.
+    fn reflections(&self, smudges: u32) -> Vec<Axis> {
+        let matching = |lines: &[u64], to_axis: fn(usize) -> Axis| {
+            mirror_distances(lines)
+                .into_iter()
+                .filter(move |&(_, distance)| distance == smudges)
+                .map(move |(idx, _)| to_axis(idx))
+        };
+        matching(&self.rows, Axis::Horizontal)
+            .chain(matching(&self.cols, Axis::Vertical))
+            .collect()
+    }
+
+    /// The (row, column) positions of the cells which differ from their
+    /// mirror image about the given axis, each paired with that mirror cell.
+    #[allow(dead_code)] // for testing
+    fn smudges(&self, axis: Axis) -> Vec<((usize, usize), (usize, usize))> {
+        let (lines, idx) = match axis {
+            Axis::Horizontal(idx) => (&self.rows, idx),
+            Axis::Vertical(idx) => (&self.cols, idx),
+        };
+        let mut result = Vec::new();
+        for (near, far) in mirrored_pairs(lines.len(), idx) {
+            let mut diff = lines[near] ^ lines[far];
+            while diff != 0 {
+                let bit = diff.trailing_zeros() as usize;
+                diff &= diff - 1;
+                result.push(match axis {
+                    Axis::Horizontal(_) => ((near, bit), (far, bit)),
+                    Axis::Vertical(_) => ((bit, near), (bit, far)),
+                });
             }
         }
+        result
     }
-    None
+}
+
+/// Pairs of line indices which are mirror images about the axis after `idx` lines.
+fn mirrored_pairs(len: usize, idx: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..idx.min(len - idx)).map(move |i| (idx - i - 1, idx + i))
+}
+
+/// For each candidate axis, the number of cells differing from their mirror image.
+fn mirror_distances(lines: &[u64]) -> Vec<(usize, u32)> {
+    (1..lines.len())
+        .map(|idx| {
+            let distance = mirrored_pairs(lines.len(), idx)
+                .map(|(near, far)| (lines[near] ^ lines[far]).count_ones())
+                .sum();
+            (idx, distance)
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -171,36 +167,56 @@ fn process_smudged_group(horizontal_lines: MutGroup) -> Option<usize> {
 mod test13 {
     use super::*;
 
-    fn to_vec(s: &str) -> Vec<char> {
-        Vec::from_iter(s.chars())
+    fn to_group(lines: &[&str]) -> Group {
+        lines.iter().map(|s| Vec::from_iter(s.chars())).collect()
     }
 
     #[test]
     #[rustfmt::skip]
-    fn GIVEN_symmetrical_line_groups_WHEN_testing_symmetry_THEN_correct() {
-        let dotest = |expected, lines: &[&str]| {
-            let owned_lines = lines.iter().map(|&s| to_vec(s)).collect::<Vec<_>>();
-            assert_eq!(expected, test_symmetry(&owned_lines, None));
+    fn GIVEN_symmetrical_line_groups_WHEN_measuring_distances_THEN_correct() {
+        let dotest = |expected: &[u32], lines: &[&str]| {
+            let pattern = Pattern::new(&to_group(lines));
+            let distances = mirror_distances(&pattern.rows)
+                .into_iter()
+                .map(|(_, distance)| distance)
+                .collect::<Vec<_>>();
+            assert_eq!(expected, distances);
         };
-        dotest(Some(2), &["0000",
-                          "1111",
-                          "1111"]);
-        dotest(Some(3), &["0000",
-                          "1111",
-                          "2222",
-                          "2222",
-                          "1111"]);
-        dotest(None, &["1111",
-                       "2222",
-                       "2222",
-                       "3333"]);
+        dotest(&[4, 0], &["....",
+                          "####",
+                          "####"]);
+        dotest(&[2, 4, 0, 2], &["....",
+                                "##..",
+                                "#..#",
+                                "#..#",
+                                "##.."]);
+        dotest(&[1, 1, 2], &["#...",
+                             "#.#.",
+                             "#.#.",
+                             "...."]);
+    }
+
+    #[test]
+    fn GIVEN_horizontal_lines_WHEN_encoding_pattern_THEN_columns_are_transposed() {
+        let pattern = Pattern::new(&to_group(&["#.#", ".##"]));
+        assert_eq!(vec![0b101, 0b110], pattern.rows);
+        assert_eq!(vec![0b01, 0b10, 0b11], pattern.cols);
     }
 
     #[test]
-    fn GIVEN_horizontal_lines_WHEN_transposing_THEN_transposed_lines_returned() {
-        let h_lines = vec![to_vec("123"), to_vec("456")];
-        let expected = vec![to_vec("14"), to_vec("25"), to_vec("36")];
-        assert_eq!(expected, transpose(&h_lines));
+    fn GIVEN_aoc_example_WHEN_finding_reflections_THEN_axes_and_smudges_reported() {
+        let groups = get_groups(EXAMPLE_INPUT);
+        let first = Pattern::new(&groups[0]);
+        assert_eq!(vec![Axis::Vertical(5)], first.reflections(0));
+        assert_eq!(vec![Axis::Horizontal(3)], first.reflections(1));
+        assert_eq!(vec![((0, 0), (5, 0))], first.smudges(Axis::Horizontal(3)));
+
+        let second = Pattern::new(&groups[1]);
+        assert_eq!(vec![Axis::Horizontal(4)], second.reflections(0));
+        assert_eq!(vec![Axis::Horizontal(1)], second.reflections(1));
+        assert_eq!(vec![((0, 4), (1, 4))], second.smudges(Axis::Horizontal(1)));
+        assert!(second.smudges(Axis::Horizontal(4)).is_empty());
+        assert!(first.smudges(Axis::Vertical(5)).is_empty());
     }
 
     static EXAMPLE_INPUT: &str = r#"#.##..##.
@@ -3,67 +3,54 @@
 //!
 //! See <https://adventofcode.com/2023/day/15>
 //!
-use std::fs;
+use std::{
+    fmt, fs,
+    hash::{BuildHasher, BuildHasherDefault, Hasher},
+};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        println!("USAGE: {} <filename>", args[0]);
+        println!("USAGE: {} <filename> [--trace]", args[0]);
         return;
     }
     let filename = &args[1];
     let contents = fs::read_to_string(filename).expect("Couldn't read file {filename}");
+    let trace = args.get(2).is_some_and(|a| a == "--trace");
 
     println!("part1 total is {}", part1(contents.as_str()));
-    println!("part2 total is {}", part2(contents.as_str()));
+    let part2 = part2(contents.as_str(), |step, library| {
+        if trace {
+            println!("After \"{step}\":\n{library}");
+        }
+    });
+    println!("part2 total is {part2}");
 }
 
 fn part1(contents: &str) -> usize {
     contents.trim().split(',').map(|s| hash(s) as usize).sum()
 }
 
-fn part2(contents: &str) -> usize {
-    let sequence = contents.trim().split(',').collect::<Vec<_>>();
-    let mut boxes: Boxes = (0..256)
-        .map(|_| Vec::<Lens>::new())
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap();
-
-    for seq in sequence {
-        if seq.contains('-') {
-            let label = seq
-                .split('-')
-                .next()
-                .unwrap_or_else(|| panic!("unable to parse {seq}"));
-            let the_box = &mut boxes[hash(label) as usize];
-            if let Some(idx) = the_box.iter().position(|l| l.label == label) {
-                the_box.remove(idx);
+fn part2(contents: &str, on_step: impl FnMut(&str, &LensLibrary)) -> usize {
+    run(contents, on_step).focusing_power()
+}
+
+/// Follow the initialization sequence, calling `on_step` with each step and
+/// the state of the boxes after it.
+fn run(contents: &str, mut on_step: impl FnMut(&str, &LensLibrary)) -> LensLibrary {
+    let mut library = LensLibrary::default();
+    for seq in contents.trim().split(',') {
+        match Step::new(seq) {
+            Step::Remove(label) => {
+                library.remove(label);
             }
-        } else {
-            let mut tokens = seq.split('=');
-            let label = tokens
-                .next()
-                .unwrap_or_else(|| panic!("unable to parse {seq}"));
-            let f_len = tokens
-                .next()
-                .unwrap_or_else(|| panic!("unable to parse {seq}"))
-                .parse::<u8>()
-                .unwrap_or_else(|_| panic!("non-numeric focal len in {seq}"));
-            let new_lens = Lens::new(label, f_len);
-            let the_box = &mut boxes[hash(label) as usize];
-            if let Some(idx) = the_box.iter().position(|l| l.label == label) {
-                the_box[idx] = new_lens;
-            } else {
-                the_box.push(new_lens);
+            Step::Insert(label, focal_len) => {
+                library.insert(label, focal_len);
             }
         }
+        on_step(seq, &library);
     }
-    boxes
-        .iter()
-        .enumerate()
-        .map(|(i, l)| focus_power(i, l))
-        .sum()
+    library
 }
 
 fn focus_power(box_number: usize, the_box: &[Lens]) -> usize {
@@ -75,15 +62,63 @@ fn focus_power(box_number: usize, the_box: &[Lens]) -> usize {
             .sum::<usize>()
 }
 
+/// The puzzle's Holiday ASCII String Helper algorithm, over any bytes.
+///
+/// Note that hashing a `str` through the `Hash` trait appends a 0xff
+/// terminator, which changes the result, so write the label's bytes directly
+/// to get the puzzle's value.
+#[derive(Debug, Default)]
+struct HolidayHasher(u8);
+
+impl Hasher for HolidayHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = bytes.iter().fold(self.0, |accum, &c| {
+            (((accum as u16 + c as u16) * 17) % 256) as u8
+        });
+    }
+
+    fn finish(&self) -> u64 {
+        self.0 as u64
+    }
+}
+
+type HolidayState = BuildHasherDefault<HolidayHasher>;
+
 fn hash(s: &str) -> u8 {
-    assert!(s.is_ascii());
-    s.bytes()
-        .fold(0, |accum, c| (((accum as u16 + c as u16) * 17) % 256) as u8)
+    assert!(
+        s.is_ascii(),
+        "HASH is defined over ASCII strings, not {s:?}"
+    );
+    let mut hasher = HolidayState::default().build_hasher();
+    hasher.write(s.as_bytes());
+    hasher.finish() as u8
 }
 
-type Boxes = [Vec<Lens>; 256];
+#[derive(Debug, Eq, PartialEq)]
+enum Step<'a> {
+    Remove(&'a str),
+    Insert(&'a str, u8),
+}
 
-#[derive(Debug)]
+impl<'a> Step<'a> {
+    fn new(seq: &'a str) -> Self {
+        if let Some(label) = seq.strip_suffix('-') {
+            return Self::Remove(label);
+        }
+        let mut tokens = seq.split('=');
+        let label = tokens
+            .next()
+            .unwrap_or_else(|| panic!("unable to parse {seq}"));
+        let f_len = tokens
+            .next()
+            .unwrap_or_else(|| panic!("unable to parse {seq}"))
+            .parse::<u8>()
+            .unwrap_or_else(|_| panic!("non-numeric focal len in {seq}"));
+        Self::Insert(label, f_len)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
 struct Lens {
     label: String,
     focal_len: u8,
@@ -96,10 +131,103 @@ impl Lens {
     }
 }
 
+/// The lenses in one box, in the order they were first inserted.
+#[derive(Debug, Default)]
+struct LensBox(Vec<Lens>);
+
+impl LensBox {
+    /// Add a lens behind the others, or swap it in for the lens already
+    /// holding its label, returning that lens's focal length.
+    fn insert(&mut self, label: &str, focal_len: u8) -> Option<u8> {
+        match self.0.iter_mut().find(|l| l.label == label) {
+            Some(lens) => Some(std::mem::replace(&mut lens.focal_len, focal_len)),
+            None => {
+                self.0.push(Lens::new(label, focal_len));
+                None
+            }
+        }
+    }
+
+    /// Take out the lens with this label, closing up the gap behind it.
+    fn remove(&mut self, label: &str) -> Option<u8> {
+        let idx = self.0.iter().position(|l| l.label == label)?;
+        Some(self.0.remove(idx).focal_len)
+    }
+
+    fn lenses(&self) -> &[Lens] {
+        &self.0
+    }
+}
+
+impl fmt::Display for LensBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lenses = self
+            .0
+            .iter()
+            .map(|l| format!("[{} {}]", l.label, l.focal_len))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lenses.join(" "))
+    }
+}
+
+/// The 256 boxes, with each label's box chosen by hashing it.
+#[derive(Debug)]
+struct LensLibrary<S = HolidayState> {
+    boxes: Vec<LensBox>,
+    hash_builder: S,
+}
+
+impl<S: Default> Default for LensLibrary<S> {
+    fn default() -> Self {
+        Self {
+            boxes: (0..256).map(|_| LensBox::default()).collect(),
+            hash_builder: S::default(),
+        }
+    }
+}
+
+impl<S: BuildHasher> LensLibrary<S> {
+    fn box_for(&mut self, label: &str) -> &mut LensBox {
+        let mut hasher = self.hash_builder.build_hasher();
+        hasher.write(label.as_bytes());
+        let idx = hasher.finish() as usize % self.boxes.len();
+        &mut self.boxes[idx]
+    }
+
+    fn insert(&mut self, label: &str, focal_len: u8) -> Option<u8> {
+        self.box_for(label).insert(label, focal_len)
+    }
+
+    fn remove(&mut self, label: &str) -> Option<u8> {
+        self.box_for(label).remove(label)
+    }
+
+    fn focusing_power(&self) -> usize {
+        self.boxes
+            .iter()
+            .enumerate()
+            .map(|(i, b)| focus_power(i, b.lenses()))
+            .sum()
+    }
+}
+
+/// Lists the boxes holding any lenses, as in the puzzle's worked example.
+impl<S> fmt::Display for LensLibrary<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, the_box) in self.boxes.iter().enumerate() {
+            if !the_box.lenses().is_empty() {
+                writeln!(f, "Box {i}: {the_box}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod test15 {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn GIVEN_known_strings_with_known_hashes_WHEN_hashing_THEN_matches_known_values() {
@@ -126,8 +254,121 @@ mod test15 {
         );
     }
 
+    #[test]
+    fn GIVEN_labels_WHEN_hashing_through_build_hasher_THEN_matches_puzzle_boxes() {
+        let state = HolidayState::default();
+        let dotest = |label: &str, h| {
+            let mut hasher = state.build_hasher();
+            hasher.write(label.as_bytes());
+            assert_eq!(h, hasher.finish());
+        };
+        dotest("rn", 0);
+        dotest("cm", 0);
+        dotest("qp", 1);
+        dotest("pc", 3);
+        // writing in pieces carries the running value across
+        let mut hasher = state.build_hasher();
+        hasher.write(b"HA");
+        hasher.write(b"SH");
+        assert_eq!(52, hasher.finish());
+    }
+
+    #[test]
+    fn GIVEN_str_keys_WHEN_stored_in_hash_map_with_holiday_state_THEN_found_again() {
+        let mut lengths = HashMap::<&str, u8, HolidayState>::default();
+        for (label, focal_len) in [("rn", 1), ("cm", 2), ("qp", 3), ("pc", 4), ("ot", 9)] {
+            lengths.insert(label, focal_len);
+        }
+        lengths.insert("qp", 7);
+        assert_eq!(5, lengths.len());
+        assert_eq!(Some(&7), lengths.get("qp"));
+        assert_eq!(Some(&9), lengths.get("ot"));
+        assert_eq!(None, lengths.get("ab"));
+        // integer keys write their native-endian bytes, which aren't ASCII
+        let squares = (0..300u32)
+            .map(|n| (n, n * n))
+            .collect::<HashMap<_, _, HolidayState>>();
+        assert_eq!(Some(&(299 * 299)), squares.get(&299));
+    }
+
+    #[test]
+    fn GIVEN_valid_steps_WHEN_parsing_THEN_matches_expected() {
+        assert_eq!(Step::Remove("cm"), Step::new("cm-"));
+        assert_eq!(Step::Insert("rn", 1), Step::new("rn=1"));
+    }
+
+    #[test]
+    fn GIVEN_lens_box_WHEN_inserting_replacing_and_removing_THEN_order_kept() {
+        let mut the_box = LensBox::default();
+        assert_eq!(None, the_box.insert("a", 1));
+        assert_eq!(None, the_box.insert("b", 2));
+        assert_eq!(None, the_box.insert("c", 3));
+        assert_eq!("[a 1] [b 2] [c 3]", the_box.to_string());
+        // replacing keeps the lens's place
+        assert_eq!(Some(1), the_box.insert("a", 7));
+        assert_eq!("[a 7] [b 2] [c 3]", the_box.to_string());
+        // removing closes the gap, and a re-inserted lens goes to the back
+        assert_eq!(Some(2), the_box.remove("b"));
+        assert_eq!(None, the_box.remove("b"));
+        assert_eq!(None, the_box.insert("b", 4));
+        assert_eq!("[a 7] [c 3] [b 4]", the_box.to_string());
+        assert_eq!(7 + 2 * 3 + 3 * 4, focus_power(0, the_box.lenses()));
+    }
+
+    #[test]
+    fn GIVEN_aoc_example_WHEN_tracing_THEN_matches_worked_example() {
+        let mut trace = String::new();
+        run(EXAMPLE, |step, library| {
+            trace += &format!("After \"{step}\":\n{library}\n");
+        });
+        assert_eq!(EXAMPLE_TRACE, trace);
+    }
+
+    static EXAMPLE_TRACE: &str = r#"After "rn=1":
+Box 0: [rn 1]
+
+After "cm-":
+Box 0: [rn 1]
+
+After "qp=3":
+Box 0: [rn 1]
+Box 1: [qp 3]
+
+After "cm=2":
+Box 0: [rn 1] [cm 2]
+Box 1: [qp 3]
+
+After "qp-":
+Box 0: [rn 1] [cm 2]
+
+After "pc=4":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4]
+
+After "ot=9":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9]
+
+After "ab=5":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9] [ab 5]
+
+After "pc-":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5]
+
+After "pc=6":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5] [pc 6]
+
+After "ot=7":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 7] [ab 5] [pc 6]
+
+"#;
+
     #[test]
     fn GIVEN_aoc_example_WHEN_part2_run_THEN_matches_expected() {
-        assert_eq!(145, part2(EXAMPLE));
+        assert_eq!(145, part2(EXAMPLE, |_, _| {}));
     }
 }
@@ -3,6 +3,7 @@
 //!
 //! See <https://adventofcode.com/2023/day/18>
 //!
+use aoc_common::polygon::RectilinearPolygon;
 use std::{fs, ops::RangeInclusive};
 
 fn main() {
@@ -69,7 +70,7 @@ fn part2(contents: &str) -> usize {
         .collect::<Vec<_>>();
 
     let vertices = get_outside_vertices(&trenches);
-    area_from_shoelace_formula(&vertices) as usize
+    RectilinearPolygon::new(vertices).signed_area() as usize
 }
 
 fn get_outside_vertices(trenches: &[Trench]) -> Vec<(i64, i64)> {
@@ -285,19 +286,6 @@ impl Trench {
     }
 }
 
-fn area_from_shoelace_formula(vertices: &[(i64, i64)]) -> i64 {
-    // https://en.wikipedia.org/wiki/Shoelace_formula
-    let total: i64 = vertices
-        .iter()
-        .enumerate()
-        .map(|(idx, xy)| {
-            let xyprime = vertices[if idx == 0 { vertices.len() - 1 } else { idx - 1 }];
-            xy.0 * xyprime.1 - xy.1 * xyprime.0
-        })
-        .sum();
-    total / 2
-}
-
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod test18 {
@@ -367,10 +355,10 @@ L 3 (#FFFFF0) "#;
 
     #[test]
     fn GIVEN_example_polygon_WHEN_calcing_area_THEN_correct_answer_returned() {
-        let square = [(0, 0), (0, 3), (3, 3), (3, 0)];
-        assert_eq!(9, area_from_shoelace_formula(&square));
-        let square = [(2, 2), (2, -2), (-2, -2), (-2, 2)];
-        assert_eq!(16, area_from_shoelace_formula(&square));
+        let square = RectilinearPolygon::new(vec![(0, 0), (0, 3), (3, 3), (3, 0)]);
+        assert_eq!(9, square.signed_area());
+        let square = RectilinearPolygon::new(vec![(2, 2), (2, -2), (-2, -2), (-2, 2)]);
+        assert_eq!(16, square.signed_area());
     }
 
     static EXAMPLE: &str = r#"
@@ -402,7 +390,7 @@ U 2 (#7a21e3)"#;
             .map(Trench::new)
             .collect::<Vec<_>>();
         let vertices = get_outside_vertices(&trenches);
-        assert_eq!(62, area_from_shoelace_formula(&vertices));
+        assert_eq!(62, RectilinearPolygon::new(vertices).signed_area());
     }
 
     #[test]
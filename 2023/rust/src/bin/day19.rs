@@ -5,6 +5,8 @@
 //!
 use std::{collections::HashMap, fs, ops::Range};
 
+use aoc_common::interval::IntervalSet;
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
@@ -47,8 +49,7 @@ fn part2(contents: &str) -> i64 {
             let result = "xmas"
                 .chars()
                 .map(|attribute| {
-                    let range = merge_attribute_predicates(predicates, attribute, 1..4001);
-                    range.end - range.start
+                    merge_attribute_predicates(predicates, attribute, 1..4001).total_length()
                 })
                 .product();
             result
@@ -251,20 +252,22 @@ fn consolidate(workflows: &HashMap<String, Workflow>) -> Vec<Vec<Predicate>> {
     recurse(workflows, workflow, Vec::new())
 }
 
+/// The values of one attribute, within `range`, that satisfy every predicate.
 fn merge_attribute_predicates(
     predicates: &[Predicate],
     attribute: char,
-    mut range: Range<i64>,
-) -> Range<i64> {
-    for p in predicates.iter() {
-        if p.attribute == attribute {
-            match p.is_less_than {
-                true => range.end = range.end.min(p.value),
-                false => range.start = range.start.max(p.value + 1),
-            }
-        }
-    }
-    range
+    range: Range<i64>,
+) -> IntervalSet<i64> {
+    predicates.iter().filter(|p| p.attribute == attribute).fold(
+        IntervalSet::from(range),
+        |accepted, p| {
+            let passing = match p.is_less_than {
+                true => i64::MIN..p.value,
+                false => p.value + 1..i64::MAX,
+            };
+            accepted.intersection(&IntervalSet::from(passing))
+        },
+    )
 }
 
 #[cfg(test)]
@@ -421,7 +424,10 @@ mod test19 {
                 is_less_than: false,
             },
         ];
-        assert_eq!(901..1000, merge_attribute_predicates(&p_list, 's', 0..4001));
+        assert_eq!(
+            IntervalSet::from(901..1000),
+            merge_attribute_predicates(&p_list, 's', 0..4001)
+        );
     }
 
     #[test]
@@ -10,6 +10,8 @@ use std::{
     ops::RangeInclusive,
 };
 
+use aoc_common::interval::IntervalMap;
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
@@ -186,7 +188,14 @@ where
 }
 
 type BrickIndex = usize;
-type ZStack = Vec<(RangeInclusive<usize>, BrickIndex)>;
+
+/// The bricks occupying one (x, y) column, by the z ranges they fill.
+type ZStack = IntervalMap<usize, BrickIndex>;
+
+/// The highest occupied z in a column, or 0 (the ground) for an empty one.
+fn top(stack: &ZStack) -> usize {
+    stack.end().map_or(0, |end| end - 1)
+}
 
 fn drop_bricks_and_return_zmap(bricks: &mut [Brick]) -> HashMap<XYCoord, ZStack> {
     let mut map: HashMap<XYCoord, ZStack> = HashMap::new();
@@ -194,49 +203,38 @@ fn drop_bricks_and_return_zmap(bricks: &mut [Brick]) -> HashMap<XYCoord, ZStack>
         let mut max_z = 0usize;
         for (x, y) in brick.iter_xy() {
             if let Some(stack) = &map.get(&(x, y)) {
-                let z = stack.last().unwrap().0.end();
-                max_z = max_z.max(*z);
+                max_z = max_z.max(top(stack));
             }
         }
         brick.z_hi.set(max_z + brick.z_len);
         for (x, y) in brick.iter_xy() {
             let stack = map.entry((x, y)).or_default();
             let lo = 1 + brick.z_hi.get() - brick.z_len;
-            stack.push((lo..=brick.z_hi.get(), idx));
+            stack.insert(lo..brick.z_hi.get() + 1, idx);
         }
     }
     map
 }
 
 fn get_supported_bricks(brick: &Brick, zmap: &HashMap<XYCoord, ZStack>) -> Vec<BrickIndex> {
-    let mut ids = Vec::new();
-    for xy in brick.iter_xy() {
-        if let Some(stack) = zmap.get(&xy) {
-            if let Some((_z, idx)) = stack
-                .iter()
-                .find(|(z, _idx)| *z.start() == brick.z_hi.get() + 1)
-            {
-                ids.push(*idx);
-            }
-        }
-    }
+    let mut ids = brick
+        .iter_xy()
+        .filter_map(|xy| zmap.get(&xy)?.get(&(brick.z_hi.get() + 1)).copied())
+        .collect::<Vec<_>>();
     ids.sort();
     ids.dedup();
     ids
 }
 
 fn get_supporting_bricks(brick: &Brick, zmap: &HashMap<XYCoord, ZStack>) -> Vec<BrickIndex> {
-    let mut ids = Vec::new();
-    for xy in brick.iter_xy() {
-        if let Some(stack) = zmap.get(&xy) {
-            if let Some((_z, idx)) = stack
-                .iter()
-                .find(|(z, _idx)| *z.end() == brick.z_hi.get() - brick.z_len)
-            {
-                ids.push(*idx);
-            }
-        }
-    }
+    let mut ids = brick
+        .iter_xy()
+        .filter_map(|xy| {
+            zmap.get(&xy)?
+                .get(&(brick.z_hi.get() - brick.z_len))
+                .copied()
+        })
+        .collect::<Vec<_>>();
     ids.sort();
     ids.dedup();
     ids
@@ -245,7 +243,7 @@ fn get_supporting_bricks(brick: &Brick, zmap: &HashMap<XYCoord, ZStack>) -> Vec<
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod test22 {
-    use std::collections::HashSet;
+    use std::{collections::HashSet, ops::Range};
 
     use super::*;
 
@@ -257,14 +255,21 @@ mod test22 {
             let exmap = HashMap::from_iter(expected);
             assert_eq!(exmap, zmap);
         };
+        let stack = |bricks: &[(Range<usize>, BrickIndex)]| {
+            let mut stack = ZStack::default();
+            for (z, idx) in bricks {
+                stack.insert(z.clone(), *idx);
+            }
+            stack
+        };
         // single stack
-        dotest("1,1,1~1,1,2", vec![((1, 1), vec![(1..=2, 0)])]);
+        dotest("1,1,1~1,1,2", vec![((1, 1), stack(&[(1..3, 0)]))]);
         // stacked bricks, requires z-sorting
         dotest(
             "1,1,6~2,1,6\n1,1,1~1,1,2",
             vec![
-                ((1, 1), vec![(1..=2, 0), (3..=3, 1)]),
-                ((2, 1), vec![(3..=3, 1)]),
+                ((1, 1), stack(&[(1..3, 0), (3..4, 1)])),
+                ((2, 1), stack(&[(3..4, 1)])),
             ],
         );
     }
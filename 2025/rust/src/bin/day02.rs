@@ -1,152 +1,103 @@
-use itertools::Itertools;
 use std::{env, fs};
 
 struct NumRange {
-    lo: u64,
-    hi: u64,
-    lo_digits: Vec<char>,
-    hi_digits: Vec<char>,
+    lo: u128,
+    hi: u128,
 }
 
 impl NumRange {
     fn parse(tok: &str) -> Self {
-        let mut toks = tok.split('-');
+        let mut toks = tok.trim().split('-');
         let first = toks.next().expect("first element missing");
         let second = toks.next().expect("second element missing");
         let lo = first.parse().expect("non integer");
         let hi = second.parse().expect("non integer");
         assert!(lo < hi);
-        Self {
-            lo,
-            hi,
-            lo_digits: first.chars().collect(),
-            hi_digits: second.chars().collect(),
-        }
+        Self { lo, hi }
     }
 
-    fn collect_prefix(&self) -> Vec<char> {
-        let mut result = Vec::<char>::new();
-        if self.lo_digits.len() == self.hi_digits.len() {
-            for (l, r) in self.lo_digits.iter().zip(self.hi_digits.iter()) {
-                if l == r {
-                    result.push(*l);
-                } else {
-                    break;
-                }
-            }
+    /// Call `func` with the sub-range of numbers having each digit count in turn.
+    fn for_each_digit_count<F: FnMut(u32, u128, u128)>(&self, mut func: F) {
+        for ndigits in num_digits(self.lo)..=num_digits(self.hi) {
+            let lo = self.lo.max(10u128.pow(ndigits - 1));
+            let hi = self.hi.min(10u128.pow(ndigits) - 1);
+            func(ndigits, lo, hi);
         }
-        result
     }
 }
 
-fn factors(n: usize) -> Vec<usize> {
-    let mut result = vec![n];
-    for i in 2..n {
-        if i * i > n {
-            break;
-        }
-        if n.is_multiple_of(i) {
-            result.push(i);
-            if i != 1 && i * i != n {
-                result.push(n / i);
+fn num_digits(n: u128) -> u32 {
+    n.checked_ilog10().unwrap_or(0) + 1
+}
+
+fn mobius(mut n: u32) -> i32 {
+    let mut result = 1;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0;
             }
+            result = -result;
         }
+        p += 1;
     }
-    result.sort();
-    result
+    if n > 1 { -result } else { result }
 }
 
-fn process_digits(ndigits: usize, divisor: usize, prefix: &[char], result: &mut Vec<Vec<char>>) {
-    //println!("nd: {}, div: {}", ndigits, divisor);
-    assert!(ndigits.is_multiple_of(divisor));
-    let pattern_length = ndigits / divisor;
-    if prefix.len() < pattern_length {
-        let range = if prefix.is_empty() {
-            '1'..='9'
-        } else {
-            '0'..='9'
-        };
-        let mut new_prefix = Vec::with_capacity(prefix.len() + 1);
-        new_prefix.extend_from_slice(prefix);
-        new_prefix.push('0');
-        for i in range {
-            *new_prefix.last_mut().unwrap() = i;
-            process_digits(ndigits, divisor, &new_prefix, result);
-        }
-    } else if prefix.len() == pattern_length {
-        let mut v = Vec::<_>::with_capacity(ndigits);
-        for _i in 0..divisor {
-            v.extend_from_slice(prefix);
-        }
-        result.push(v);
-    } else {
-        // prefix is more than pattern_length
-        let mut v = Vec::<_>::with_capacity(ndigits);
-        v.extend_from_slice(prefix);
-        v.resize(ndigits, ' ');
-        for i in 0..pattern_length {
-            for j in 1..divisor {
-                let idx = i + pattern_length * j;
-                if idx < prefix.len() {
-                    if prefix[idx] != prefix[i] {
-                        return;
-                    }
-                } else {
-                    v[idx] = v[i];
-                }
-            }
-        }
-        result.push(v);
+/// The sum of the `ndigits`-digit numbers in `[lo, hi]` made of a block of
+/// `block_len` digits repeated. Each is the block times the repunit-like
+/// multiplier `(10^ndigits - 1) / (10^block_len - 1)`, e.g. 1001 for 3-digit
+/// blocks in 6-digit numbers, so the blocks in range form an arithmetic series.
+fn sum_repeated(ndigits: u32, block_len: u32, lo: u128, hi: u128) -> u128 {
+    assert!(ndigits.is_multiple_of(block_len));
+    let multiplier = (10u128.pow(ndigits) - 1) / (10u128.pow(block_len) - 1);
+    let first = lo.div_ceil(multiplier).max(10u128.pow(block_len - 1));
+    let last = (hi / multiplier).min(10u128.pow(block_len) - 1);
+    if first > last {
+        return 0;
     }
+    // One of these two factors is even; halve it before multiplying so the
+    // product never exceeds the sum itself.
+    let (sum, count) = (first + last, last - first + 1);
+    let pairs = if sum.is_multiple_of(2) {
+        sum / 2 * count
+    } else {
+        sum * (count / 2)
+    };
+    multiplier * pairs
 }
 
-fn p1_process(range: &NumRange) -> Vec<u64> {
-    let common_prefix: Vec<char> = range.collect_prefix();
-    let mut candidates = Vec::<_>::new();
-    let mut ndigits = range.lo_digits.len();
-    while ndigits <= range.hi_digits.len() {
+/// The sum of the IDs in the range made of some digits repeated exactly twice.
+fn p1_sum(range: &NumRange) -> u128 {
+    let mut total = 0;
+    range.for_each_digit_count(|ndigits, lo, hi| {
         if ndigits.is_multiple_of(2) {
-            process_digits(ndigits, 2, &common_prefix, &mut candidates);
+            total += sum_repeated(ndigits, ndigits / 2, lo, hi);
         }
-        ndigits += 1;
-    }
-    candidates
-        .into_iter()
-        .filter_map(|v| {
-            let val: u64 = v.iter().collect::<String>().parse().unwrap();
-            if val >= range.lo && val <= range.hi {
-                Some(val)
-            } else {
-                None
-            }
-        })
-        .collect()
+    });
+    total
 }
 
-fn p2_process(range: &NumRange) -> Vec<u64> {
-    let common_prefix: Vec<char> = range.collect_prefix();
-    let mut candidates = Vec::<_>::new();
-    let mut ndigits = range.lo_digits.len();
-    while ndigits <= range.hi_digits.len() {
-        for i in factors(ndigits) {
-            if i > 1 {
-                process_digits(ndigits, i, &common_prefix, &mut candidates);
+/// The sum of the IDs in the range made of some digits repeated at least twice.
+fn p2_sum(range: &NumRange) -> u128 {
+    // A number repeats with some period if it repeats with period ndigits / q
+    // for a prime q. Inclusion-exclusion over the sets of such primes, whose
+    // product m is squarefree, gives the coefficient -mobius(m) for the sum
+    // of numbers with period ndigits / m.
+    let mut total = 0;
+    range.for_each_digit_count(|ndigits, lo, hi| {
+        let mut signed_total = 0i128;
+        for m in 2..=ndigits {
+            if ndigits.is_multiple_of(m) {
+                let sum = sum_repeated(ndigits, ndigits / m, lo, hi) as i128;
+                signed_total -= mobius(m) as i128 * sum;
             }
         }
-        ndigits += 1;
-    }
-    candidates
-        .into_iter()
-        .filter_map(|v| {
-            let val: u64 = v.iter().collect::<String>().parse().unwrap();
-            if val >= range.lo && val <= range.hi {
-                Some(val)
-            } else {
-                None
-            }
-        })
-        .unique()
-        .collect()
+        total += signed_total as u128;
+    });
+    total
 }
 
 fn main() {
@@ -159,9 +110,8 @@ fn main() {
     let mut p1_score = 0;
     let mut p2_score = 0;
     for range in ranges {
-        // println!("{}-{}", range.lo, range.hi);
-        p1_score += p1_process(&range).iter().sum::<u64>();
-        p2_score += p2_process(&range).iter().sum::<u64>();
+        p1_score += p1_sum(&range);
+        p2_score += p2_sum(&range);
     }
     println!("part1: {}", p1_score);
     println!("part2: {}", p2_score);
@@ -171,73 +121,96 @@ fn main() {
 mod tester {
     use super::*;
 
+    fn is_doubled(n: u128) -> bool {
+        let s = n.to_string();
+        let (l, r) = s.split_at(s.len() / 2);
+        s.len().is_multiple_of(2) && l == r
+    }
+
+    fn is_repeated(n: u128) -> bool {
+        let s = n.to_string();
+        (1..s.len()).any(|len| s.len().is_multiple_of(len) && s == s[..len].repeat(s.len() / len))
+    }
+
     #[test]
-    fn prefix_test() {
-        let r = NumRange::parse("123456-123567");
-        assert_eq!(r.collect_prefix(), "123".chars().collect::<Vec<_>>());
+    fn p1_sum_test() {
+        let dotest = |range, expected: &[u128]| {
+            assert_eq!(p1_sum(&NumRange::parse(range)), expected.iter().sum());
+        };
+        dotest("11-22", &[11, 22]);
+        dotest("99-115", &[99]);
+        dotest("998-1012", &[1010]);
+        dotest("1188511880-1188511890", &[1188511885]);
+        dotest("222220-222224", &[222222]);
+        dotest("1698522-1698528", &[]);
+        dotest("446443-446449", &[446446]);
+        dotest("38593856-38593862", &[38593859]);
     }
 
     #[test]
-    fn p1_process_test() {
-        assert_eq!(p1_process(&NumRange::parse("11-22")), vec![11, 22]);
-        assert_eq!(p1_process(&NumRange::parse("99-115")), vec![99]);
-        assert_eq!(p1_process(&NumRange::parse("998-1012")), vec![1010]);
-        assert_eq!(
-            p1_process(&NumRange::parse("1188511880-1188511890")),
-            vec![1188511885]
-        );
-        assert_eq!(p1_process(&NumRange::parse("222220-222224")), vec![222222]);
-        assert_eq!(p1_process(&NumRange::parse("1698522-1698528")), vec![]);
-        assert_eq!(p1_process(&NumRange::parse("446443-446449")), vec![446446]);
-        assert_eq!(
-            p1_process(&NumRange::parse("38593856-38593862")),
-            vec![38593859]
-        );
+    fn p2_sum_test() {
+        let dotest = |range, expected: &[u128]| {
+            assert_eq!(p2_sum(&NumRange::parse(range)), expected.iter().sum());
+        };
+        dotest("2727216511-2727316897", &[2727227272, 2727272727]);
+        dotest("1-22", &[11, 22]);
+        dotest("11-22", &[11, 22]);
+        dotest("99-115", &[99, 111]);
+        dotest("998-1012", &[999, 1010]);
+        dotest("1188511880-1188511890", &[1188511885]);
+        dotest("222220-222224", &[222222]);
+        dotest("1698522-1698528", &[]);
+        dotest("446443-446449", &[446446]);
+        dotest("38593856-38593862", &[38593859]);
+        dotest("565653-565659", &[565656]);
+        dotest("824824821-824824827", &[824824824]);
+        dotest("2121212118-2121212124", &[2121212121]);
     }
 
     #[test]
-    fn p2_process_test() {
-        assert_eq!(
-            p2_process(&NumRange::parse("2727216511-2727316897")),
-            vec![2727227272, 2727272727]
-        );
-        assert_eq!(p2_process(&NumRange::parse("1-22")), vec![11, 22]);
-        assert_eq!(p2_process(&NumRange::parse("11-22")), vec![11, 22]);
-        assert_eq!(p2_process(&NumRange::parse("99-115")), vec![99, 111]);
-        assert_eq!(p2_process(&NumRange::parse("998-1012")), vec![999, 1010]);
-        assert_eq!(
-            p2_process(&NumRange::parse("1188511880-1188511890")),
-            vec![1188511885]
-        );
-        assert_eq!(p2_process(&NumRange::parse("222220-222224")), vec![222222]);
-        assert_eq!(p2_process(&NumRange::parse("1698522-1698528")), vec![]);
-        assert_eq!(p2_process(&NumRange::parse("446443-446449")), vec![446446]);
-        assert_eq!(
-            p2_process(&NumRange::parse("38593856-38593862")),
-            vec![38593859]
-        );
-        assert_eq!(p2_process(&NumRange::parse("565653-565659")), vec![565656]);
-        assert_eq!(
-            p2_process(&NumRange::parse("824824821-824824827")),
-            vec![824824824]
-        );
-        assert_eq!(
-            p2_process(&NumRange::parse("2121212118-2121212124")),
-            vec![2121212121]
-        );
+    fn mobius_test() {
+        let expected = [1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0];
+        for (n, mu) in (1..).zip(expected) {
+            assert_eq!(mobius(n), mu, "mobius({n})");
+        }
+    }
+
+    #[test]
+    fn brute_force_test() {
+        for (lo, hi) in [(1, 20000), (95, 1_000_100), (123_000, 129_000)] {
+            let range = NumRange { lo, hi };
+            let doubled: u128 = (lo..=hi).filter(|&n| is_doubled(n)).sum();
+            let repeated: u128 = (lo..=hi).filter(|&n| is_repeated(n)).sum();
+            assert_eq!(p1_sum(&range), doubled, "{lo}-{hi}");
+            assert_eq!(p2_sum(&range), repeated, "{lo}-{hi}");
+        }
+    }
+
+    #[test]
+    fn huge_range_test() {
+        let range = NumRange::parse("100000000000-999999999999");
+        let blocks: u128 = (100000..=999999).sum();
+        assert_eq!(p1_sum(&range), blocks * 1000001);
+
+        // 36-digit numbers from 999999999999999990 followed by 18 zeros: the
+        // doubled ones have the last ten 18-digit blocks, and the only number
+        // with a shorter period is all nines, which is also doubled.
+        let lo = "999999999999999990".to_string() + &"0".repeat(18);
+        let range = NumRange::parse(&format!("{lo}-{}", "9".repeat(36)));
+        let top_block = 10u128.pow(18) - 1;
+        let blocks: u128 = (top_block - 9..=top_block).sum();
+        assert_eq!(p1_sum(&range), blocks * (10u128.pow(18) + 1));
+        assert_eq!(p2_sum(&range), p1_sum(&range));
     }
 
     #[test]
-    fn factors_test() {
-        assert_eq!(factors(1), vec![1]);
-        assert_eq!(factors(2), vec![2]);
-        assert_eq!(factors(3), vec![3]);
-        assert_eq!(factors(4), vec![2, 4]);
-        assert_eq!(factors(5), vec![5]);
-        assert_eq!(factors(6), vec![2, 3, 6]);
-        assert_eq!(factors(7), vec![7]);
-        assert_eq!(factors(8), vec![2, 4, 8]);
-        assert_eq!(factors(9), vec![3, 9]);
-        assert_eq!(factors(10), vec![2, 5, 10]);
+    fn near_overflow_test() {
+        // The top three doubled 38-digit numbers sum to about 3e38, just under
+        // u128::MAX, though twice that does not fit.
+        let lo = "9999999999999999997".to_string() + &"0".repeat(19);
+        let range = NumRange::parse(&format!("{lo}-{}", "9".repeat(38)));
+        let top_block = 10u128.pow(19) - 1;
+        let blocks: u128 = (top_block - 2..=top_block).sum();
+        assert_eq!(p1_sum(&range), blocks * (10u128.pow(19) + 1));
     }
 }
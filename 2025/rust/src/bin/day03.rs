@@ -1,68 +1,65 @@
 use std::{env, fs};
 
-fn to_int(chars: &[char]) -> u64 {
-    let mut result = 0;
-    let mut mult = 1;
-    let zero = '0' as u64;
-    for c in chars.iter().rev() {
-        result += (*c as u64 - zero) * mult;
-        mult *= 10;
-    }
-    result
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Extreme {
+    Largest,
+    #[allow(dead_code)] // for testing
+    Smallest,
 }
 
-fn largest_pair(line_str: &str) -> u64 {
-    let line: Vec<_> = line_str.chars().collect();
-    let mut first = None;
-    let mut second = None;
-    for i in 0..line.len() {
-        let next = line[i];
-        if let Some(cur) = first {
-            if next > cur && i != line.len() - 1 {
-                first = Some(next);
-                second = None;
-                continue;
-            }
-        } else {
-            first = Some(next);
-            continue;
-        }
-        if let Some(cur) = second
-            && next < cur
-        {
-            continue;
-        }
-        second = Some(next);
-    }
-    to_int(&[first.unwrap(), second.unwrap()])
+/// Digits picked out of a bank, in order, with their positions in the bank.
+#[derive(Debug, PartialEq)]
+struct Selection {
+    digits: Vec<u8>,
+    positions: Vec<usize>,
 }
 
-fn largest(line: &[char], ndigits: usize, result: &mut Vec<char>) {
-    if 0 == ndigits {
-        return;
+impl Selection {
+    /// The selected digits read as a number. 38 digits is the most that
+    /// always fits in a u128.
+    fn value(&self) -> u128 {
+        assert!(self.digits.len() <= 38, "too many digits for u128");
+        self.digits
+            .iter()
+            .fold(0, |acc, &digit| acc * 10 + digit as u128)
     }
-    assert!(line.len() >= ndigits);
-    let usable_len = line.len() - ndigits + 1;
-    let mut idx = None;
-    for i in 0..usable_len {
-        if let Some(max_idx) = idx
-            && line[i] <= line[max_idx]
-        {
-            continue;
+}
+
+/// Choose `k` digits from the bank, keeping their order, to make the largest
+/// (or smallest) possible number. A monotonic stack of positions is kept: a new
+/// digit displaces the stacked digits it beats, as long as enough digits remain
+/// in the bank to still make up `k`.
+fn select_digits(bank: &[u8], k: usize, extreme: Extreme) -> Selection {
+    assert!(
+        k <= bank.len(),
+        "can't select {k} digits from {}",
+        bank.len()
+    );
+    let beats = |new: u8, old: u8| match extreme {
+        Extreme::Largest => new > old,
+        Extreme::Smallest => new < old,
+    };
+    let mut positions = Vec::<usize>::with_capacity(k);
+    for (idx, &digit) in bank.iter().enumerate() {
+        assert!(digit.is_ascii_digit(), "bad digit {}", digit as char);
+        let remaining = bank.len() - idx;
+        while let Some(&top) = positions.last() {
+            if positions.len() - 1 + remaining >= k && beats(digit, bank[top]) {
+                positions.pop();
+            } else {
+                break;
+            }
+        }
+        if positions.len() < k {
+            positions.push(idx);
         }
-        idx = Some(i);
     }
-    let pos = idx.expect("empty line");
-    result.push(line[pos]);
-    let next_line = &line[pos + 1..];
-    largest(next_line, ndigits - 1, result);
+    let digits = positions.iter().map(|&idx| bank[idx] - b'0').collect();
+    Selection { digits, positions }
 }
 
-fn largest_12(line_str: &str) -> u64 {
-    let line: Vec<_> = line_str.chars().collect();
-    let mut result = Vec::<char>::with_capacity(12);
-    largest(&line, 12, &mut result);
-    to_int(&result)
+fn joltage(line: &str, k: usize) -> u128 {
+    select_digits(line.as_bytes(), k, Extreme::Largest).value()
 }
 
 fn main() {
@@ -72,29 +69,92 @@ fn main() {
     }
     let contents = fs::read_to_string(&argv[1]).expect("unable to read file");
     let lines: Vec<_> = contents.split("\n").filter(|l| !l.is_empty()).collect();
-    let p1: u64 = lines.clone().into_iter().map(largest_pair).sum();
+    let p1: u128 = lines.iter().map(|line| joltage(line, 2)).sum();
     println!("part1: {}", p1);
-    let p2: u64 = lines.into_iter().map(largest_12).sum();
+    let p2: u128 = lines.iter().map(|line| joltage(line, 12)).sum();
     println!("part2: {}", p2);
 }
 
 #[cfg(test)]
 mod tester {
     use super::*;
+    use aoc_common::test_util::XorShift;
 
     #[test]
     fn largest_pair_test() {
-        assert_eq!(largest_pair("987654321111111"), 98);
-        assert_eq!(largest_pair("811111111111119"), 89);
-        assert_eq!(largest_pair("234234234234278"), 78);
-        assert_eq!(largest_pair("818181911112111"), 92);
+        assert_eq!(joltage("987654321111111", 2), 98);
+        assert_eq!(joltage("811111111111119", 2), 89);
+        assert_eq!(joltage("234234234234278", 2), 78);
+        assert_eq!(joltage("818181911112111", 2), 92);
     }
 
     #[test]
     fn largest_12_test() {
-        assert_eq!(largest_12("987654321111111"), 987654321111);
-        assert_eq!(largest_12("811111111111119"), 811111111119);
-        assert_eq!(largest_12("234234234234278"), 434234234278);
-        assert_eq!(largest_12("818181911112111"), 888911112111);
+        assert_eq!(joltage("987654321111111", 12), 987654321111);
+        assert_eq!(joltage("811111111111119", 12), 811111111119);
+        assert_eq!(joltage("234234234234278", 12), 434234234278);
+        assert_eq!(joltage("818181911112111", 12), 888911112111);
+    }
+
+    #[test]
+    fn positions_test() {
+        let selection = select_digits(b"818181911112111", 2, Extreme::Largest);
+        assert_eq!(selection.digits, vec![9, 2]);
+        assert_eq!(selection.positions, vec![6, 11]);
+        let selection = select_digits(b"818181911112111", 3, Extreme::Smallest);
+        assert_eq!(selection.digits, vec![1, 1, 1]);
+        assert_eq!(selection.positions, vec![1, 3, 5]);
+        let selection = select_digits(b"5432", 2, Extreme::Smallest);
+        assert_eq!(selection.value(), 32);
+    }
+
+    #[test]
+    fn u128_test() {
+        let bank = "9".repeat(40) + "1";
+        assert_eq!(joltage(&bank, 38), 10u128.pow(38) - 1);
+        let bank = "1234567890".repeat(5);
+        assert_eq!(
+            joltage(&bank, 38),
+            "94567890123456789012345678901234567890".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn brute_force_test() {
+        fn brute_force(bank: &[u8], k: usize, extreme: Extreme) -> u128 {
+            let n = bank.len();
+            let candidates = (0u32..1 << n).filter(|mask| mask.count_ones() as usize == k);
+            let values = candidates.map(|mask| {
+                (0..n)
+                    .filter(|i| mask & (1 << i) != 0)
+                    .fold(0, |acc, i| acc * 10 + (bank[i] - b'0') as u128)
+            });
+            match extreme {
+                Extreme::Largest => values.max().unwrap(),
+                Extreme::Smallest => values.min().unwrap(),
+            }
+        }
+
+        let mut rng = XorShift::new(0x9e3779b97f4a7c15);
+        for _ in 0..500 {
+            let n = 1 + rng.below(12) as usize;
+            let bank = (0..n)
+                .map(|_| b'0' + rng.below(10) as u8)
+                .collect::<Vec<_>>();
+            let k = 1 + rng.below(n as u64) as usize;
+            for extreme in [Extreme::Largest, Extreme::Smallest] {
+                let selection = select_digits(&bank, k, extreme);
+                assert_eq!(
+                    selection.value(),
+                    brute_force(&bank, k, extreme),
+                    "{} k={k} {extreme:?}",
+                    String::from_utf8_lossy(&bank)
+                );
+                assert!(selection.positions.is_sorted());
+                for (&pos, &digit) in selection.positions.iter().zip(&selection.digits) {
+                    assert_eq!(bank[pos] - b'0', digit);
+                }
+            }
+        }
     }
 }
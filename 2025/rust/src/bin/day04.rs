@@ -1,14 +1,5 @@
 use std::{env, fs};
 
-fn get(grid: &[Vec<char>], x: usize, y: usize, dx: i32, dy: i32) -> Option<char> {
-    let xp = x as i32 + dx;
-    let yp = y as i32 + dy;
-    if xp < 0 || yp < 0 || xp as usize >= grid[0].len() || yp as usize >= grid.len() {
-        return None;
-    }
-    Some(grid[yp as usize][xp as usize])
-}
-
 const DIRS: [[i32; 2]; 8] = [
     [-1, -1],
     [-1, 0],
@@ -20,32 +11,91 @@ const DIRS: [[i32; 2]; 8] = [
     [1, 1],
 ];
 
-fn part1(grid: &[Vec<char>]) -> Vec<[usize; 2]> {
-    let mut result = Vec::new();
+/// Rolls with fewer than this many neighbouring rolls can be reached by a forklift.
+const ACCESS_THRESHOLD: usize = 4;
+
+fn neighbours(grid: &[Vec<char>], x: usize, y: usize) -> impl Iterator<Item = [usize; 2]> {
+    let (width, height) = (grid[0].len() as i32, grid.len() as i32);
+    DIRS.iter().filter_map(move |[dx, dy]| {
+        let xp = x as i32 + dx;
+        let yp = y as i32 + dy;
+        if xp < 0 || yp < 0 || xp >= width || yp >= height {
+            return None;
+        }
+        Some([xp as usize, yp as usize])
+    })
+}
+
+/// For each cell, the wave in which its roll is removed: wave 1 holds the rolls
+/// accessible at the start, wave 2 those accessible once wave 1 is gone, and so
+/// on. Empty cells and rolls which are never removed are `None`.
+///
+/// Each roll's neighbour count is kept up to date as rolls are removed, so a
+/// removal only re-examines its eight neighbours rather than the whole grid.
+fn removal_waves(grid: &[Vec<char>], threshold: usize) -> Vec<Vec<Option<usize>>> {
+    let mut counts = vec![vec![0usize; grid[0].len()]; grid.len()];
+    let mut waves = vec![vec![None; grid[0].len()]; grid.len()];
+    let mut worklist = Vec::new();
     for (y, row) in grid.iter().enumerate() {
         for (x, point) in row.iter().enumerate() {
             if *point != '@' {
-                // print!("{}", *point);
                 continue;
             }
-            let mut count = 0;
-            for [dx, dy] in DIRS {
-                if get(grid, x, y, dx, dy) == Some('@') {
-                    count += 1;
-                }
+            counts[y][x] = neighbours(grid, x, y)
+                .filter(|[xn, yn]| grid[*yn][*xn] == '@')
+                .count();
+            if counts[y][x] < threshold {
+                waves[y][x] = Some(1);
+                worklist.push([x, y]);
             }
-            if count < 4 {
-                result.push([x, y]);
-                // print!("{}", 'x');
-            } else {
-                // print!("{}", *point);
+        }
+    }
+    let mut wave = 1;
+    while !worklist.is_empty() {
+        let mut next_worklist = Vec::new();
+        for [x, y] in worklist {
+            for [xn, yn] in neighbours(grid, x, y) {
+                if grid[yn][xn] != '@' || waves[yn][xn].is_some() {
+                    continue;
+                }
+                counts[yn][xn] -= 1;
+                if counts[yn][xn] < threshold {
+                    waves[yn][xn] = Some(wave + 1);
+                    next_worklist.push([xn, yn]);
+                }
             }
         }
-        // println!("");
+        worklist = next_worklist;
+        wave += 1;
+    }
+    waves
+}
+
+/// Render the removal waves with one character per cell: the wave number in
+/// base 36, `@` for rolls which stay and `.` for empty cells. Base 36 runs out
+/// of digits at `z`, so every wave from 36 on is drawn as `+`.
+fn heat_map(grid: &[Vec<char>], waves: &[Vec<Option<usize>>]) -> String {
+    let mut result = String::new();
+    for (row, wave_row) in grid.iter().zip(waves) {
+        for (point, wave) in row.iter().zip(wave_row) {
+            result.push(match wave {
+                Some(wave) => char::from_digit(*wave as u32, 36).unwrap_or('+'),
+                None => *point,
+            });
+        }
+        result.push('\n');
     }
     result
 }
 
+fn part1(grid: &[Vec<char>], threshold: usize) -> usize {
+    removal_waves(grid, threshold)
+        .iter()
+        .flatten()
+        .filter(|wave| **wave == Some(1))
+        .count()
+}
+
 fn make_grid(contents: &str) -> Vec<Vec<char>> {
     contents
         .lines()
@@ -59,31 +109,29 @@ fn make_grid(contents: &str) -> Vec<Vec<char>> {
         .collect()
 }
 
-fn part2(grid: &mut [Vec<char>]) -> usize {
-    let mut p2_score = 0;
-    loop {
-        let positions = part1(grid);
-        let nremoved = positions.len();
-        if nremoved == 0 {
-            break;
-        }
-        p2_score += nremoved;
-        for [x, y] in positions {
-            grid[y][x] = '.';
-        }
-    }
-    p2_score
+fn part2(grid: &[Vec<char>], threshold: usize) -> usize {
+    removal_waves(grid, threshold)
+        .iter()
+        .flatten()
+        .filter(|wave| wave.is_some())
+        .count()
 }
 
 fn main() {
     let argv: Vec<_> = env::args().collect();
-    if argv.len() != 2 {
-        panic!("USAGE: {} <input.dat>", argv[0]);
+    if !(2..=3).contains(&argv.len()) {
+        panic!("USAGE: {} <input.dat> [--heat-map]", argv[0]);
     }
     let contents = fs::read_to_string(&argv[1]).expect("invalid filename");
-    let mut grid = make_grid(&contents);
-    println!("part1: {}", part1(&grid).len());
-    println!("part2: {}", part2(&mut grid));
+    let grid = make_grid(&contents);
+    if argv.get(2).is_some_and(|arg| arg == "--heat-map") {
+        print!(
+            "{}",
+            heat_map(&grid, &removal_waves(&grid, ACCESS_THRESHOLD))
+        );
+    }
+    println!("part1: {}", part1(&grid, ACCESS_THRESHOLD));
+    println!("part2: {}", part2(&grid, ACCESS_THRESHOLD));
 }
 
 #[cfg(test)]
@@ -106,12 +154,47 @@ mod tester {
     #[test]
     fn part1_test() {
         let grid = make_grid(TEST_DATA);
-        assert_eq!(part1(&grid).len(), 13);
+        assert_eq!(part1(&grid, ACCESS_THRESHOLD), 13);
     }
 
     #[test]
     fn part2_test() {
-        let mut grid = make_grid(TEST_DATA);
-        assert_eq!(part2(&mut grid), 43);
+        let grid = make_grid(TEST_DATA);
+        assert_eq!(part2(&grid, ACCESS_THRESHOLD), 43);
+    }
+
+    #[test]
+    fn heat_map_test() {
+        let grid = make_grid(TEST_DATA);
+        let waves = removal_waves(&grid, ACCESS_THRESHOLD);
+        // the first wave matches the puzzle's marked-up example
+        let first_wave = heat_map(&grid, &waves)
+            .chars()
+            .map(|c| match c {
+                '1' => 'x',
+                '.' | '\n' => c,
+                _ => '@',
+            })
+            .collect::<String>();
+        assert_eq!(
+            first_wave,
+            "..xx.xx@x.\nx@@.@.@.@@\n@@@@@.x.@@\n@.@@@@..@.\nx@.@@@@.@x\n\
+             .@@@@@@@.@\n.@.@.@.@@@\nx.@@@.@@@@\n.@@@@@@@@.\nx.x.@@@.x.\n"
+        );
+        let mut wave_sizes = vec![0; 9];
+        for wave in waves.iter().flatten().flatten() {
+            wave_sizes[wave - 1] += 1;
+        }
+        assert_eq!(wave_sizes, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn threshold_test() {
+        let grid = make_grid(TEST_DATA);
+        let rolls = TEST_DATA.chars().filter(|c| *c == '@').count();
+        assert_eq!(part1(&grid, 0), 0);
+        assert_eq!(part2(&grid, 9), rolls);
+        assert_eq!(part1(&grid, 9), rolls);
+        assert!(part2(&grid, 3) < part2(&grid, 4));
     }
 }
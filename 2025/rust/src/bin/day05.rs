@@ -1,3 +1,5 @@
+use aoc_common::interval::IntervalSet;
+
 fn main() {
     let argv = std::env::args().collect::<Vec<_>>();
     assert!(argv.len() == 2);
@@ -6,8 +8,10 @@ fn main() {
     println!("part2 score: {}", part2(&contents));
 }
 
-fn parse(contents: &str) -> (Vec<[u64; 2]>, Vec<u64>) {
-    let mut ranges = Vec::new();
+/// The fresh ID ranges, which are inclusive in the input, and the available
+/// IDs.
+fn parse(contents: &str) -> (IntervalSet<u64>, Vec<u64>) {
+    let mut ranges = IntervalSet::new();
     let mut ids = Vec::new();
     let mut first_block = true;
     for line in contents.lines().map(str::trim) {
@@ -20,12 +24,10 @@ fn parse(contents: &str) -> (Vec<[u64; 2]>, Vec<u64>) {
                 let val: u64 = t.parse().expect("non-integer in range");
                 val
             });
-            let range = [
-                toks.next().expect("lo range"),
-                toks.next().expect("hi range"),
-            ];
-            assert!(range[0] <= range[1]);
-            ranges.push(range);
+            let lo = toks.next().expect("lo range");
+            let hi = toks.next().expect("hi range");
+            assert!(lo <= hi);
+            ranges.insert(lo..hi + 1);
         } else {
             ids.push(line.parse::<u64>().expect("non-integer id"));
         }
@@ -35,48 +37,12 @@ fn parse(contents: &str) -> (Vec<[u64; 2]>, Vec<u64>) {
 
 fn part1(contents: &str) -> u64 {
     let (ranges, ids) = parse(contents);
-    let mut p1_score = 0;
-    'outer: for id in ids {
-        for range in &ranges {
-            if id >= range[0] && id <= range[1] {
-                // println!("range: {range:?}, id: {id}");
-                p1_score += 1;
-                continue 'outer;
-            }
-        }
-    }
-    p1_score
+    ids.iter().filter(|id| ranges.contains(id)).count() as u64
 }
 
 fn part2(contents: &str) -> u64 {
-    let (mut ranges, _) = parse(contents);
-    let mut unique_ranges = Vec::new();
-    'outer: while !ranges.is_empty() {
-        let last = ranges.last().unwrap();
-        for i in 0..(ranges.len() - 1) {
-            if let Some(merged) = merge_ranges(last, &ranges[i]) {
-                ranges[i] = merged;
-                ranges.truncate(ranges.len() - 1);
-                continue 'outer;
-            }
-        }
-        unique_ranges.push(*last);
-        ranges.truncate(ranges.len() - 1);
-    }
-    unique_ranges
-        .into_iter()
-        .map(|range| range[1] - range[0] + 1)
-        .sum()
-}
-
-fn merge_ranges(lhs: &[u64; 2], rhs: &[u64; 2]) -> Option<[u64; 2]> {
-    if (lhs[0] >= rhs[0] && lhs[0] <= rhs[1])
-        || (lhs[1] >= rhs[0] && lhs[1] <= rhs[1])
-        || (rhs[0] >= lhs[0] && rhs[0] <= lhs[1])
-    {
-        return Some([std::cmp::min(lhs[0], rhs[0]), std::cmp::max(lhs[1], rhs[1])]);
-    }
-    None
+    let (ranges, _) = parse(contents);
+    ranges.total_length()
 }
 
 #[cfg(test)]
@@ -100,13 +66,19 @@ mod tester {
 
     #[test]
     fn test_merge() {
-        assert_eq!(merge_ranges(&[1, 5], &[5, 10]), Some([1, 10]));
-        assert_eq!(merge_ranges(&[1, 5], &[6, 10]), None);
-        assert_eq!(merge_ranges(&[5, 9], &[2, 5]), Some([2, 9]));
-        assert_eq!(merge_ranges(&[1, 7], &[5, 10]), Some([1, 10]));
-        assert_eq!(merge_ranges(&[3, 9], &[2, 5]), Some([2, 9]));
-        assert_eq!(merge_ranges(&[3, 9], &[4, 4]), Some([3, 9]));
-        assert_eq!(merge_ranges(&[4, 5], &[3, 9]), Some([3, 9]));
+        let merge = |lhs: &str, rhs: &str| parse(&format!("{lhs}\n{rhs}\n")).0;
+        assert_eq!(merge("1-5", "5-10"), IntervalSet::from(1..11));
+        // touching inclusive ranges leave no gap, so they merge too
+        assert_eq!(merge("1-5", "6-10"), IntervalSet::from(1..11));
+        assert_eq!(
+            merge("1-5", "7-10"),
+            IntervalSet::from_ranges([1..6, 7..11])
+        );
+        assert_eq!(merge("5-9", "2-5"), IntervalSet::from(2..10));
+        assert_eq!(merge("1-7", "5-10"), IntervalSet::from(1..11));
+        assert_eq!(merge("3-9", "2-5"), IntervalSet::from(2..10));
+        assert_eq!(merge("3-9", "4-4"), IntervalSet::from(3..10));
+        assert_eq!(merge("4-5", "3-9"), IntervalSet::from(3..10));
     }
 
     #[test]
@@ -1,142 +1,161 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Operation {
     Add,
+    Sub,
     Mult,
+    Div,
 }
 
 impl Operation {
     fn parse(tok: char) -> Self {
         match tok {
             '+' => Self::Add,
+            '-' => Self::Sub,
             '*' => Self::Mult,
+            '/' => Self::Div,
             _ => panic!("unrecognized operation \"{tok}\""),
         }
     }
+
+    fn apply(&self, lhs: i128, rhs: i128) -> Option<i128> {
+        match self {
+            Self::Add => lhs.checked_add(rhs),
+            Self::Sub => lhs.checked_sub(rhs),
+            Self::Mult => lhs.checked_mul(rhs),
+            Self::Div => lhs.checked_div(rhs),
+        }
+    }
 }
 
+#[derive(Debug, PartialEq)]
 struct MathProblem {
-    numbers: Vec<i64>,
-    op: Option<Operation>,
+    numbers: Vec<i128>,
+    op: Operation,
 }
 
 impl MathProblem {
-    fn answer(&self) -> i64 {
-        if let Some(op) = &self.op {
-            match op {
-                Operation::Add => self.numbers.iter().sum(),
-                Operation::Mult => self.numbers.iter().fold(1, |a, b| a * *b),
-            }
-        } else {
-            panic!("operation on incomplete problem");
-        }
+    /// The numbers combined left to right, or None on overflow or division by zero.
+    fn answer(&self) -> Option<i128> {
+        let (first, rest) = self.numbers.split_first()?;
+        rest.iter()
+            .try_fold(*first, |acc, number| self.op.apply(acc, *number))
     }
 }
 
-fn get_tokens_list(contents: &str) -> Vec<Vec<&str>> {
-    contents
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(|line| {
-            line.split(' ')
-                .filter_map(|tok| {
-                    let t = tok.trim();
-                    if t.is_empty() {
-                        return None;
-                    }
-                    Some(t)
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect()
+/// A block of columns holding one problem, with its operation.
+struct Block {
+    start: usize,
+    end: usize,
+    op: Operation,
 }
 
-fn part1(tokens_list: &[Vec<&str>]) -> i64 {
-    let line_count = tokens_list.len();
-    let mut problems = Vec::<MathProblem>::new();
-    for (i, tokens) in tokens_list.iter().enumerate() {
-        if i < line_count - 1 {
-            for (j, number) in tokens
-                .iter()
-                .map(|t| t.parse::<i64>().expect("non-integer"))
-                .enumerate()
-            {
-                if i > 0 {
-                    problems[j].numbers.push(number);
-                } else {
-                    let mut numbers = Vec::<i64>::with_capacity(line_count - 1);
-                    numbers.push(number);
-                    problems.push(MathProblem { numbers, op: None });
-                }
-            }
-        } else {
-            for (j, op) in tokens
-                .iter()
-                .map(|&s| Operation::parse(s.chars().next().unwrap()))
-                .enumerate()
-            {
-                problems[j].op = Some(op);
-            }
-        }
-    }
-    problems.iter().map(MathProblem::answer).sum()
+/// The worksheet as a grid of characters, with lines padded to equal length,
+/// split into problem blocks at the columns which are blank on every line.
+struct Worksheet {
+    lines: Vec<Vec<char>>,
+    blocks: Vec<Block>,
 }
 
-fn part2(contents: &str) -> i64 {
-    let mut lines = contents
-        .lines()
-        .filter(|&line| !line.trim().is_empty())
-        .map(|line| line.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let mut op_idx_list = Vec::new();
-    for (idx, ch) in lines.last().expect("not enough lines").iter().enumerate() {
-        if *ch != ' ' {
-            op_idx_list.push((Operation::parse(*ch), idx));
+impl Worksheet {
+    fn parse(contents: &str) -> Self {
+        let mut lines = contents
+            .lines()
+            .filter(|&line| !line.trim().is_empty())
+            .map(|line| line.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let width = lines.iter().map(Vec::len).max().expect("empty worksheet");
+        for line in lines.iter_mut() {
+            line.resize(width, ' ');
         }
-    }
-    lines.truncate(lines.len() - 1);
-    let mut score = 0;
-    for (op, idx) in op_idx_list {
-        let mut digit_list = Vec::new();
-        let mut col_idx = idx;
-        'outer: loop {
-            let mut blank = true;
-            for line in lines.iter() {
-                if line.len() > col_idx && line[col_idx] != ' ' {
-                    blank = false;
-                    break;
-                }
+        let blank = |col: usize| lines.iter().all(|line| line[col] == ' ');
+        let op_line = lines.last().unwrap();
+        let mut blocks = Vec::new();
+        let mut col = 0;
+        while col < width {
+            if blank(col) {
+                col += 1;
+                continue;
             }
-            if !blank {
-                let mut col_digits = Vec::<char>::with_capacity(lines.len());
-                for line in lines.iter() {
-                    col_digits.push(line[col_idx]);
-                }
-                digit_list.push(col_digits);
-                col_idx += 1;
-            } else {
-                break 'outer;
+            let start = col;
+            while col < width && !blank(col) {
+                col += 1;
             }
+            let ops = op_line[start..col]
+                .iter()
+                .filter(|&&c| c != ' ')
+                .collect::<Vec<_>>();
+            let op = match ops[..] {
+                [op] => Operation::parse(*op),
+                _ => panic!("expected one operation in columns {start}..{col}, found {ops:?}"),
+            };
+            blocks.push(Block {
+                start,
+                end: col,
+                op,
+            });
         }
-        let mp = MathProblem {
-            numbers: digit_list.into_iter().map(|col| to_int(&col)).collect(),
-            op: Some(op),
-        };
-        score += mp.answer();
+        Self { lines, blocks }
     }
-    score
-}
 
-fn to_int(chars: &[char]) -> i64 {
-    let mut result = 0;
-    let mut mult = 1;
-    let zero = '0' as i64;
-    for c in chars.iter().rev() {
-        if *c == ' ' {
-            continue;
-        }
-        result += (*c as i64 - zero) * mult;
-        mult *= 10;
+    fn number_lines(&self) -> &[Vec<char>] {
+        &self.lines[..self.lines.len() - 1]
     }
-    result
+
+    /// The problems with each number written along a row, top to bottom.
+    fn row_problems(&self) -> Vec<MathProblem> {
+        self.blocks
+            .iter()
+            .map(|block| MathProblem {
+                numbers: self
+                    .number_lines()
+                    .iter()
+                    .filter_map(|line| to_int(line[block.start..block.end].iter()))
+                    .collect(),
+                op: block.op,
+            })
+            .collect()
+    }
+
+    /// The problems with each number written down a column, read right to left.
+    fn column_problems(&self) -> Vec<MathProblem> {
+        self.blocks
+            .iter()
+            .map(|block| MathProblem {
+                numbers: (block.start..block.end)
+                    .rev()
+                    .filter_map(|col| to_int(self.number_lines().iter().map(|line| &line[col])))
+                    .collect(),
+                op: block.op,
+            })
+            .collect()
+    }
+}
+
+/// The digits among the characters read as a number, or None if there are none.
+fn to_int<'a>(chars: impl Iterator<Item = &'a char>) -> Option<i128> {
+    chars.filter(|c| **c != ' ').fold(None, |acc, c| {
+        let digit = c.to_digit(10).unwrap_or_else(|| panic!("non-digit {c}"));
+        Some(acc.unwrap_or(0) * 10 + digit as i128)
+    })
+}
+
+fn total(problems: &[MathProblem]) -> i128 {
+    problems
+        .iter()
+        .map(|problem| {
+            problem
+                .answer()
+                .unwrap_or_else(|| panic!("no answer for {problem:?}"))
+        })
+        .sum()
+}
+
+fn part1(worksheet: &Worksheet) -> i128 {
+    total(&worksheet.row_problems())
+}
+
+fn part2(worksheet: &Worksheet) -> i128 {
+    total(&worksheet.column_problems())
 }
 
 fn main() {
@@ -146,8 +165,63 @@ fn main() {
     }
 
     let contents = std::fs::read_to_string(&argv[1]).expect("non existent filename");
-    let tokens_list = get_tokens_list(&contents);
+    let worksheet = Worksheet::parse(&contents);
+
+    println!("part1: {}", part1(&worksheet));
+    println!("part2: {}", part2(&worksheet));
+}
+
+#[cfg(test)]
+mod tester {
+    use super::*;
+
+    const TEST_DATA: &str = "123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  \n";
 
-    println!("part1: {}", part1(&tokens_list));
-    println!("part2: {}", part2(&contents));
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&Worksheet::parse(TEST_DATA)), 4277556);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(&Worksheet::parse(TEST_DATA)), 3263827);
+    }
+
+    #[test]
+    fn test_readings() {
+        let worksheet = Worksheet::parse(TEST_DATA);
+        assert_eq!(
+            worksheet.row_problems()[3],
+            MathProblem {
+                numbers: vec![64, 23, 314],
+                op: Operation::Add
+            }
+        );
+        assert_eq!(
+            worksheet.column_problems()[3],
+            MathProblem {
+                numbers: vec![4, 431, 623],
+                op: Operation::Add
+            }
+        );
+        assert_eq!(
+            worksheet.column_problems()[0],
+            MathProblem {
+                numbers: vec![356, 24, 1],
+                op: Operation::Mult
+            }
+        );
+    }
+
+    #[test]
+    fn test_operations() {
+        let worksheet = Worksheet::parse("100 12\n  7  3\n-   / \n");
+        assert_eq!(part1(&worksheet), 93 + 4);
+        // columns right to left: 7 - 0 - 1 and 23 / 1
+        assert_eq!(part2(&worksheet), 6 + 23);
+        let problem = |numbers: Vec<i128>, op| MathProblem { numbers, op };
+        assert_eq!(problem(vec![i128::MAX, 2], Operation::Mult).answer(), None);
+        assert_eq!(problem(vec![5, 0], Operation::Div).answer(), None);
+        assert_eq!(problem(vec![], Operation::Add).answer(), None);
+    }
 }
@@ -1,10 +1,18 @@
-use std::collections::HashMap;
-
 fn main() {
     let argv = std::env::args().collect::<Vec<_>>();
+    if !(2..=3).contains(&argv.len()) {
+        panic!("USAGE: {} <input.dat> [--trace]", argv[0]);
+    }
     let contents = std::fs::read_to_string(&argv[1]).expect("invalid filename");
-    println!("part1: {}", part1(&contents));
-    println!("part2: {}", part2(&contents));
+    let grid = make_grid(&contents);
+    let trace = argv.get(2).is_some_and(|arg| arg == "--trace");
+    let (splits, timelines) = sweep(&grid, |row, beams| {
+        if trace {
+            println!("{}", format_row(row, beams));
+        }
+    });
+    println!("part1: {}", splits);
+    println!("part2: {}", timelines);
 }
 
 fn make_grid(contents: &str) -> Vec<Vec<char>> {
@@ -18,90 +26,98 @@ fn make_grid(contents: &str) -> Vec<Vec<char>> {
     grid
 }
 
-fn part1(contents: &str) -> usize {
-    let mut grid = make_grid(contents);
+/// Sweep the beams down the manifold a row at a time, keeping the number of
+/// timelines in which a beam occupies each column. A splitter hit by beams in
+/// k timelines passes k on to each side, so the total at the bottom is the
+/// number of timelines. Returns that total along with the number of splitters
+/// hit, and passes each row's beam multiplicities to `on_row`.
+fn sweep<F>(grid: &[Vec<char>], mut on_row: F) -> (usize, u128)
+where
+    F: FnMut(&[char], &[u128]),
+{
     let ncols = grid[0].len();
-
-    let mut count = 0;
-    for row_idx in 1..grid.len() {
-        for col_idx in 0..ncols {
-            let has_parent = matches!(grid[row_idx - 1][col_idx], '|' | 'S');
-            match grid[row_idx][col_idx] {
-                '.' if has_parent => grid[row_idx][col_idx] = '|',
-                '^' if has_parent => {
-                    let mut d = &mut grid[row_idx][col_idx - 1];
-                    if col_idx > 0 {
-                        *d = '|';
-                    }
-                    d = &mut grid[row_idx][col_idx + 1];
-                    if col_idx < ncols - 1 {
-                        *d = '|';
-                    }
-                    count += 1;
+    let mut beams = grid[0]
+        .iter()
+        .map(|&c| u128::from(c == 'S'))
+        .collect::<Vec<_>>();
+    on_row(&grid[0], &beams);
+    let mut splits = 0;
+    for row in &grid[1..] {
+        let mut next = vec![0u128; ncols];
+        for (col_idx, &count) in beams.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            if row[col_idx] == '^' {
+                splits += 1;
+                if col_idx > 0 {
+                    next[col_idx - 1] += count;
+                }
+                if col_idx < ncols - 1 {
+                    next[col_idx + 1] += count;
                 }
-                _ => (),
+            } else {
+                next[col_idx] += count;
             }
         }
+        beams = next;
+        on_row(row, &beams);
     }
-    for line in grid {
-        println!("{}", String::from_iter(line.iter()));
-    }
+    (splits, beams.iter().sum())
+}
 
-    count
+/// The row with beams drawn in, followed by the multiplicity of each beam.
+fn format_row(row: &[char], beams: &[u128]) -> String {
+    let drawn = row
+        .iter()
+        .zip(beams)
+        .map(|(&c, &count)| if count > 0 && c == '.' { '|' } else { c })
+        .collect::<String>();
+    let counts = beams
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(u128::to_string)
+        .collect::<Vec<_>>();
+    format!("{drawn} {}", counts.join(" "))
 }
 
-type Point = (usize, usize);
-type Cache = HashMap<Point, usize>;
+#[cfg(test)]
+mod tester {
+    use super::*;
 
-fn part2(contents: &str) -> usize {
-    let mut grid = make_grid(contents);
-    let mut cache = Cache::new();
-    count_paths_recursive(&mut grid, &mut cache, 1)
-}
+    const TEST_DATA: &str = r#"
+.......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............
+"#;
 
-fn count_paths_recursive(grid: &mut Vec<Vec<char>>, cache: &mut Cache, row_idx: usize) -> usize {
-    if row_idx == grid.len() {
-        return 1;
+    #[test]
+    fn test_sweep() {
+        let grid = make_grid(TEST_DATA);
+        assert_eq!(sweep(&grid, |_, _| ()), (21, 40));
     }
-    let mut result = 0;
-    let ncols = grid[0].len();
-    for col_idx in 0..ncols {
-        let mut paths_below = 0;
-        let incoming_beam = matches!(grid[row_idx - 1][col_idx], '|' | 'S');
-        match grid[row_idx][col_idx] {
-            '.' if incoming_beam => {
-                // the splitter rows are interspersed with blank rows,
-                // so we can just memoize the straight beam locations:
-                let key = (row_idx, col_idx);
-                if let Some(entry) = cache.get(&key) {
-                    paths_below = *entry;
-                } else {
-                    let copy = grid[row_idx][col_idx];
-                    grid[row_idx][col_idx] = '|';
-                    paths_below += count_paths_recursive(grid, cache, row_idx + 1);
-                    grid[row_idx][col_idx] = copy;
-                    cache.insert(key, paths_below);
-                }
-            }
-            '^' if incoming_beam => {
-                if col_idx > 0 {
-                    let idx = col_idx - 1;
-                    let copy = grid[row_idx][idx];
-                    grid[row_idx][idx] = '|';
-                    paths_below += count_paths_recursive(grid, cache, row_idx + 1);
-                    grid[row_idx][idx] = copy;
-                }
-                if col_idx < ncols - 1 {
-                    let idx = col_idx + 1;
-                    let copy = grid[row_idx][idx];
-                    grid[row_idx][idx] = '|';
-                    paths_below += count_paths_recursive(grid, cache, row_idx + 1);
-                    grid[row_idx][idx] = copy;
-                }
-            }
-            _ => (),
-        }
-        result += paths_below;
+
+    #[test]
+    fn test_format_row() {
+        let grid = make_grid(TEST_DATA);
+        let mut rows = Vec::new();
+        sweep(&grid, |row, beams| rows.push(format_row(row, beams)));
+        assert_eq!(rows[0], ".......S....... 1");
+        assert_eq!(rows[2], "......|^|...... 1 1");
+        assert_eq!(rows[4], ".....|^|^|..... 1 2 1");
+        assert_eq!(rows[15], "|.|.|.|.|.|||.| 1 2 10 11 11 2 1 1 1");
     }
-    result
 }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{cmp::Reverse, collections::BinaryHeap};
 
 fn main() {
     let argv = std::env::args().collect::<Vec<_>>();
@@ -7,31 +7,28 @@ fn main() {
     }
     let contents = std::fs::read_to_string(&argv[1]).expect("invalid filename");
     let points = Point::parse_points(&contents);
-    println!("part1: {}", part1(&points));
+    println!("part1: {}", part1(&points, 1000));
     println!("part2: {}", part2(&points));
 }
 
-fn part1(points: &[Point]) -> usize {
-    let pairs = PointPair::get_pairs(points);
-    let groups = process_groupings(&pairs[0..1000], |_, _| true);
-    let mut sizes: Vec<usize> = groups.into_iter().map(|group| group.len()).collect();
+fn part1(points: &[Point], connections: usize) -> usize {
+    let mut circuits = DisjointSet::new(points.len());
+    for (i, j) in NearestPairs::new(points).take(connections) {
+        circuits.union(i, j);
+    }
+    let mut sizes = circuits.set_sizes();
     sizes.sort();
-    // println!("{sizes:?}");
     sizes.into_iter().rev().take(3).product()
 }
 
 fn part2(points: &[Point]) -> i64 {
-    let distances = PointPair::get_pairs(points);
-    let mut answer = 0;
-    let callback = |single_group_len: usize, last_pair: &(Point, Point)| {
-        if single_group_len == points.len() {
-            answer = last_pair.0.x * last_pair.1.x;
-            return false;
+    let mut circuits = DisjointSet::new(points.len());
+    for (i, j) in NearestPairs::new(points) {
+        if circuits.union(i, j) && circuits.num_sets() == 1 {
+            return points[i].x * points[j].x;
         }
-        true
-    };
-    process_groupings(&distances, callback);
-    answer
+    }
+    panic!("points never formed a single circuit");
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Hash, Eq)]
@@ -68,86 +65,161 @@ impl Point {
             .map(Point::new)
             .collect::<Vec<_>>()
     }
+    fn distance_squared(&self, other: &Point) -> i64 {
+        let dx = self.x - other.x;
+        let dy = self.y - other.y;
+        let dz = self.z - other.z;
+        dx * dx + dy * dy + dz * dz
+    }
 }
 
-#[derive(PartialEq, PartialOrd, Debug)]
-struct PointPair {
-    distance: f64,
-    points: (Point, Point),
+/// Yields the index pairs of the points in order of increasing distance apart.
+/// Every one of the n(n-1)/2 pairs is materialised up front, so this holds
+/// O(n^2) memory however few are taken. They are heapified rather than sorted,
+/// so taking only the nearest k costs O(n^2 + k log n) time.
+struct NearestPairs {
+    heap: BinaryHeap<Reverse<(i64, usize, usize)>>,
 }
 
-impl PointPair {
-    fn new(p1: &Point, p2: &Point) -> Self {
-        let dx = p1.x - p2.x;
-        let dy = p1.y - p2.y;
-        let dz = p1.z - p2.z;
-        let distance = ((dx * dx + dy * dy + dz * dz) as f64).sqrt();
+impl NearestPairs {
+    fn new(points: &[Point]) -> Self {
+        let n = points.len();
+        let mut pairs = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+        for i in 0..n {
+            for j in (i + 1)..n {
+                pairs.push(Reverse((points[i].distance_squared(&points[j]), i, j)));
+            }
+        }
         Self {
-            points: (*p1, *p2),
-            distance,
+            heap: BinaryHeap::from(pairs),
         }
     }
+}
 
-    fn get_pairs(points: &[Point]) -> Vec<Self> {
-        let n = points.len();
-        let mut distances = Vec::with_capacity(n / 2 * (n + 1));
-        for i in 0..points.len() {
-            for j in (i + 1)..points.len() {
-                distances.push(PointPair::new(&points[i], &points[j]));
-            }
-        }
-        distances.sort_by(|lhs, rhs| {
-            lhs.distance
-                .partial_cmp(&rhs.distance)
-                .expect("unexpected NaN!")
-        });
-        distances
+impl Iterator for NearestPairs {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.heap.pop().map(|Reverse((_, i, j))| (i, j))
     }
 }
 
-fn process_groupings<F>(pairs: &[PointPair], mut callback: F) -> Vec<HashSet<Point>>
-where
-    F: FnMut(usize, &(Point, Point)) -> bool,
-{
-    let mut groups = Vec::<HashSet<Point>>::new();
-    for PointPair {
-        distance: _,
-        points,
-    } in pairs.iter()
-    {
-        let mut group_idxs = (None, None);
-        for (i, group) in groups.iter().enumerate() {
-            if group.contains(&points.0) {
-                group_idxs.0 = Some(i);
-            }
-            if group.contains(&points.1) {
-                group_idxs.1 = Some(i);
-            }
+/// Union-find over the indices 0..n, with path compression and union by size.
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    num_sets: usize,
+}
+
+impl DisjointSet {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            num_sets: n,
         }
-        if let Some(i) = group_idxs.0
-            && let Some(j) = group_idxs.1
-        {
-            if i != j {
-                let last = i.max(j);
-                let first = i.min(j);
-                let tmp = groups.remove(last);
-                groups[first].extend(tmp);
-            }
-        } else if let Some(i) = group_idxs.0 {
-            groups[i].insert(points.1);
-        } else if let Some(i) = group_idxs.1 {
-            groups[i].insert(points.0);
-        } else {
-            groups.push(HashSet::from([points.0, points.1]));
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
         }
-        let single_group_size = match groups.len() {
-            1 => groups[0].len(),
-            _ => 0,
-        };
-        if !callback(single_group_size, points) {
-            break;
+        let mut x = x;
+        while self.parent[x] != root {
+            x = std::mem::replace(&mut self.parent[x], root);
         }
+        root
+    }
+
+    /// Merge the sets containing `a` and `b`, returning false if they were
+    /// already the same set.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.num_sets -= 1;
+        true
+    }
+
+    fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+
+    /// The size of every set, in no particular order.
+    fn set_sizes(&self) -> Vec<usize> {
+        (0..self.parent.len())
+            .filter(|&x| self.parent[x] == x)
+            .map(|root| self.size[root])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tester {
+    use super::*;
+    const TEST_DATA: &str = r#"162,817,812
+57,618,57
+906,360,560
+592,479,940
+352,342,300
+466,668,158
+542,29,236
+431,825,988
+739,650,466
+52,470,668
+216,146,977
+819,987,18
+117,168,530
+805,96,715
+346,949,466
+970,615,88
+941,993,340
+862,61,35
+984,92,344
+425,690,689"#;
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(&Point::parse_points(TEST_DATA), 10), 40);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(&Point::parse_points(TEST_DATA)), 25272);
+    }
+
+    #[test]
+    fn test_disjoint_set() {
+        let mut set = DisjointSet::new(6);
+        assert_eq!(set.num_sets(), 6);
+        assert!(set.union(0, 1));
+        assert!(set.union(2, 3));
+        assert!(set.union(1, 3));
+        assert!(!set.union(0, 2));
+        assert_eq!(set.num_sets(), 3);
+        assert_eq!(set.find(0), set.find(3));
+        assert_ne!(set.find(0), set.find(4));
+        let mut sizes = set.set_sizes();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 1, 4]);
+    }
+
+    #[test]
+    fn test_nearest_pairs() {
+        let points = Point::parse_points(TEST_DATA);
+        let nearest = NearestPairs::new(&points).take(3).collect::<Vec<_>>();
+        assert_eq!(nearest, vec![(0, 19), (0, 7), (2, 13)]);
+        let all = NearestPairs::new(&points)
+            .map(|(i, j)| points[i].distance_squared(&points[j]))
+            .collect::<Vec<_>>();
+        assert_eq!(all.len(), 190);
+        assert!(all.is_sorted());
     }
-    // println!("groups: {groups:?}");
-    groups
 }
@@ -0,0 +1,266 @@
+//!
+//! A set of bytes stored as a 256-bit bitmap.
+//!
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct ByteSet([u64; 4]);
+
+impl ByteSet {
+    pub const fn new() -> Self {
+        Self([0; 4])
+    }
+
+    pub const fn all() -> Self {
+        Self([u64::MAX; 4])
+    }
+
+    /// Build a set from a slice without branching on the bytes.
+    ///
+    /// Each byte's word and bit come straight from its value, and the slice is
+    /// read in chunks spread over separate accumulators so that consecutive
+    /// updates don't wait on one another.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut accumulators = [[0u64; 4]; 4];
+        let mut chunks = bytes.chunks_exact(4);
+        for chunk in &mut chunks {
+            for (words, &b) in accumulators.iter_mut().zip(chunk) {
+                words[(b >> 6) as usize] |= 1 << (b & 63);
+            }
+        }
+        for (words, &b) in accumulators.iter_mut().zip(chunks.remainder()) {
+            words[(b >> 6) as usize] |= 1 << (b & 63);
+        }
+        accumulators
+            .into_iter()
+            .fold(Self::new(), |set, words| set | Self(words))
+    }
+
+    /// Add a byte, returning whether it was newly added.
+    pub fn insert(&mut self, b: u8) -> bool {
+        let added = !self.contains(b);
+        self.0[(b >> 6) as usize] |= 1 << (b & 63);
+        added
+    }
+
+    /// Take a byte out, returning whether it was present.
+    pub fn remove(&mut self, b: u8) -> bool {
+        let present = self.contains(b);
+        self.0[(b >> 6) as usize] &= !(1 << (b & 63));
+        present
+    }
+
+    /// Add the byte if it is absent and remove it if it is present.
+    pub fn toggle(&mut self, b: u8) {
+        self.0[(b >> 6) as usize] ^= 1 << (b & 63);
+    }
+
+    pub fn contains(&self, b: u8) -> bool {
+        self.0[(b >> 6) as usize] & (1 << (b & 63)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// The smallest byte in the set.
+    pub fn first(&self) -> Option<u8> {
+        self.0
+            .iter()
+            .enumerate()
+            .find(|(_, &w)| w != 0)
+            .map(|(idx, w)| (idx * 64) as u8 + w.trailing_zeros() as u8)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        *self | *other
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        *self & *other
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        *self - *other
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        *self ^ *other
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    /// The bytes in increasing order.
+    pub fn iter(&self) -> Iter {
+        Iter {
+            words: self.0,
+            word: 0,
+        }
+    }
+}
+
+macro_rules! wordwise_op {
+    ($trait:ident, $method:ident, |$a:ident, $b:ident| $combine:expr) => {
+        impl $trait for ByteSet {
+            type Output = Self;
+
+            fn $method(self, other: Self) -> Self {
+                let mut words = [0; 4];
+                for (w, ($a, $b)) in words.iter_mut().zip(self.0.into_iter().zip(other.0)) {
+                    *w = $combine;
+                }
+                Self(words)
+            }
+        }
+    };
+}
+
+wordwise_op!(BitOr, bitor, |a, b| a | b);
+wordwise_op!(BitAnd, bitand, |a, b| a & b);
+wordwise_op!(BitXor, bitxor, |a, b| a ^ b);
+wordwise_op!(Sub, sub, |a, b| a & !b);
+
+impl FromIterator<u8> for ByteSet {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for b in iter {
+            set.insert(b);
+        }
+        set
+    }
+}
+
+impl IntoIterator for ByteSet {
+    type Item = u8;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for ByteSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Iterates over a [`ByteSet`] by clearing the lowest set bit of each word.
+pub struct Iter {
+    words: [u64; 4],
+    word: usize,
+}
+
+impl Iterator for Iter {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        while self.word < 4 {
+            let w = &mut self.words[self.word];
+            if *w != 0 {
+                let bit = w.trailing_zeros();
+                *w &= *w - 1;
+                return Some((self.word * 64) as u8 + bit as u8);
+            }
+            self.word += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use super::*;
+    use crate::test_util::XorShift;
+
+    fn random_bytes(rng: &mut XorShift, len: usize) -> Vec<u8> {
+        (0..len).map(|_| rng.next_u64() as u8).collect()
+    }
+
+    fn brute_force(bytes: &[u8]) -> [bool; 256] {
+        let mut present = [false; 256];
+        for &b in bytes {
+            present[b as usize] = true;
+        }
+        present
+    }
+
+    fn members(present: [bool; 256]) -> Vec<u8> {
+        (0..=255u8).filter(|&b| present[b as usize]).collect()
+    }
+
+    #[test]
+    fn GIVEN_bytes_across_all_words_WHEN_inserting_and_removing_THEN_membership_tracked() {
+        let mut set = ByteSet::new();
+        assert!(set.is_empty());
+        assert_eq!(None, set.first());
+        for b in [255, 0, 64, 63, 128, 191] {
+            assert!(set.insert(b));
+            assert!(!set.insert(b));
+        }
+        assert_eq!(
+            vec![0, 63, 64, 128, 191, 255],
+            set.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(6, set.len());
+        assert_eq!(Some(0), set.first());
+        assert!(set.remove(0));
+        assert!(!set.remove(0));
+        assert_eq!(Some(63), set.first());
+        set.toggle(63);
+        set.toggle(1);
+        assert_eq!(vec![1, 64, 128, 191, 255], set.iter().collect::<Vec<_>>());
+        assert_eq!(256, ByteSet::all().len());
+        // 'a' and '!' are 64 apart, so must not share a bit
+        assert!(!ByteSet::from_bytes(b"a").contains(b'!'));
+    }
+
+    #[test]
+    fn GIVEN_random_bytes_WHEN_combining_sets_THEN_matches_brute_force() {
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        for _ in 0..2000 {
+            let len_a = rng.below(40) as usize;
+            let a = random_bytes(&mut rng, len_a);
+            let len_b = rng.below(40) as usize;
+            let b = random_bytes(&mut rng, len_b);
+            let (set_a, set_b) = (ByteSet::from_bytes(&a), ByteSet::from_bytes(&b));
+            let (present_a, present_b) = (brute_force(&a), brute_force(&b));
+            assert_eq!(set_a, a.iter().copied().collect::<ByteSet>());
+
+            let combine = |op: fn(bool, bool) -> bool| {
+                members(std::array::from_fn(|i| op(present_a[i], present_b[i])))
+            };
+            assert_eq!(combine(|x, _| x), set_a.iter().collect::<Vec<_>>());
+            assert_eq!(
+                combine(|x, y| x || y),
+                set_a.union(&set_b).iter().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                combine(|x, y| x && y),
+                set_a.intersection(&set_b).iter().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                combine(|x, y| x && !y),
+                set_a.difference(&set_b).iter().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                combine(|x, y| x != y),
+                set_a
+                    .symmetric_difference(&set_b)
+                    .iter()
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(combine(|x, y| x && !y).is_empty(), set_a.is_subset(&set_b));
+            assert_eq!(members(present_a).len(), set_a.len());
+            assert_eq!(members(present_a).first().copied(), set_a.first());
+        }
+    }
+}
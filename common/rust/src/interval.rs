@@ -0,0 +1,678 @@
+//!
+//! Sets of integers stored as sorted, disjoint half-open ranges, piecewise
+//! translations of those sets, and ranges labelled with values.
+//!
+use std::ops::{Add, Range, Sub};
+
+///
+/// A set of values stored as a list of half-open ranges.
+///
+/// The list is kept normalised: the ranges are non-empty, sorted by start,
+/// and neither overlap nor touch, so two sets holding the same values always
+/// compare equal. `T::default()` is taken to be zero.
+///
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// Build a set from ranges in any order, which may be empty, overlap or
+    /// touch one another.
+    pub fn from_ranges(ranges: impl IntoIterator<Item = Range<T>>) -> Self {
+        let mut sorted = ranges
+            .into_iter()
+            .filter(|r| r.start < r.end)
+            .collect::<Vec<_>>();
+        sorted.sort_by_key(|r| r.start);
+        let mut normalised: Vec<Range<T>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match normalised.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => normalised.push(range),
+            }
+        }
+        Self { ranges: normalised }
+    }
+
+    /// The normalised ranges making up the set, in increasing order.
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The smallest value in the set.
+    pub fn start(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    /// One past the largest value in the set.
+    pub fn end(&self) -> Option<T> {
+        self.ranges.last().map(|r| r.end)
+    }
+
+    /// The number of values in the set.
+    pub fn total_length(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::default(), |total, r| total + (r.end - r.start))
+    }
+
+    /// Add a range to the set, merging it with any ranges it overlaps or
+    /// touches.
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.start >= range.end {
+            return;
+        }
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let mut merged = range;
+        if first < last {
+            merged.start = merged.start.min(self.ranges[first].start);
+            merged.end = merged.end.max(self.ranges[last - 1].end);
+        }
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let idx = self.ranges.partition_point(|r| r.end <= *value);
+        self.ranges.get(idx).is_some_and(|r| r.start <= *value)
+    }
+
+    /// Whether every value in the range is in the set. An empty range is
+    /// always contained.
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        if range.start >= range.end {
+            return true;
+        }
+        let idx = self.ranges.partition_point(|r| r.end <= range.start);
+        self.ranges
+            .get(idx)
+            .is_some_and(|r| r.start <= range.start && range.end <= r.end)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.ranges.iter().all(|r| other.contains_range(r))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_ranges(self.ranges.iter().chain(other.ranges.iter()).cloned())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            // whichever range finishes first can't overlap anything further on
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        // the pieces of two normalised sets can't touch, so no merging needed
+        Self { ranges }
+    }
+
+    /// The values in this set that are not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for range in self.ranges.iter() {
+            let mut start = range.start;
+            // skip the ranges of other that finish before this one starts
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                if other.ranges[k].start > start {
+                    ranges.push(start..other.ranges[k].start);
+                }
+                start = start.max(other.ranges[k].end);
+                k += 1;
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        Self { ranges }
+    }
+}
+
+impl<T> From<Range<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>,
+{
+    fn from(range: Range<T>) -> Self {
+        Self::from_ranges([range])
+    }
+}
+
+impl<T> FromIterator<Range<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>,
+{
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        Self::from_ranges(iter)
+    }
+}
+
+///
+/// A piecewise-linear map made of translated source ranges.
+///
+/// Each piece sends the values of its source range to a destination range of
+/// the same length, in the same order. Values outside every source range map
+/// to themselves. Maps compose, so a chain of them can be collapsed into one
+/// whose size depends on the breakpoints rather than the length of the chain.
+///
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RangeMap<T> {
+    /// (source range, destination start), sorted by source start.
+    pieces: Vec<(Range<T>, T)>,
+}
+
+impl<T> RangeMap<T>
+where
+    T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>,
+{
+    /// Build a map from (source range, destination start) pieces. Panics if
+    /// two source ranges overlap, as the map would then be ambiguous.
+    ///
+    /// Pieces that leave their values in place are dropped and neighbouring
+    /// pieces with the same shift are joined, so equal maps compare equal.
+    pub fn new(pieces: impl IntoIterator<Item = (Range<T>, T)>) -> Self {
+        let mut pieces = pieces
+            .into_iter()
+            .filter(|(src, _)| src.start < src.end)
+            .collect::<Vec<_>>();
+        pieces.sort_by_key(|(src, _)| src.start);
+        assert!(
+            pieces.windows(2).all(|w| w[0].0.end <= w[1].0.start),
+            "source ranges overlap"
+        );
+        let mut normalised: Vec<(Range<T>, T)> = Vec::with_capacity(pieces.len());
+        for (src, dst) in pieces {
+            if dst == src.start {
+                continue;
+            }
+            match normalised.last_mut() {
+                Some((last, last_dst))
+                    if last.end == src.start && *last_dst + (last.end - last.start) == dst =>
+                {
+                    last.end = src.end
+                }
+                _ => normalised.push((src, dst)),
+            }
+        }
+        Self { pieces: normalised }
+    }
+
+    /// The (source range, destination start) pieces, sorted by source start.
+    pub fn pieces(&self) -> &[(Range<T>, T)] {
+        &self.pieces
+    }
+
+    /// The values moved by one of the pieces.
+    pub fn domain(&self) -> IntervalSet<T> {
+        IntervalSet::from_ranges(self.pieces.iter().map(|(src, _)| src.clone()))
+    }
+
+    pub fn map(&self, value: T) -> T {
+        let idx = self.pieces.partition_point(|(src, _)| src.end <= value);
+        match self.pieces.get(idx) {
+            Some((src, dst)) if src.start <= value => *dst + (value - src.start),
+            _ => value,
+        }
+    }
+
+    /// The image of a whole set under the map.
+    pub fn map_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut image = set.difference(&self.domain()).ranges;
+        for (src, dst) in self.pieces.iter() {
+            let moved = set.intersection(&IntervalSet::from(src.clone()));
+            image.extend(moved.ranges.iter().map(|r| {
+                let start = *dst + (r.start - src.start);
+                start..start + (r.end - r.start)
+            }));
+        }
+        IntervalSet::from_ranges(image)
+    }
+
+    /// The values that the map sends into `set`.
+    pub fn preimage(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut preimage = set.difference(&self.domain()).ranges;
+        for (src, dst) in self.pieces.iter() {
+            let image = IntervalSet::from(*dst..*dst + (src.end - src.start));
+            preimage.extend(set.intersection(&image).ranges.iter().map(|r| {
+                let start = src.start + (r.start - *dst);
+                start..start + (r.end - r.start)
+            }));
+        }
+        IntervalSet::from_ranges(preimage)
+    }
+
+    /// The map that applies this one and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let mut pieces = Vec::new();
+        for (src, dst) in self.pieces.iter() {
+            for (segment, segment_dst) in next.segments(*dst..*dst + (src.end - src.start)) {
+                let start = src.start + (segment.start - *dst);
+                pieces.push((start..start + (segment.end - segment.start), segment_dst));
+            }
+        }
+        // values this map leaves alone are only moved by next
+        let domain = self.domain();
+        for (src, dst) in next.pieces.iter() {
+            let untouched = IntervalSet::from(src.clone()).difference(&domain);
+            pieces.extend(
+                untouched
+                    .ranges
+                    .into_iter()
+                    .map(|r| (r.start..r.end, *dst + (r.start - src.start))),
+            );
+        }
+        Self::new(pieces)
+    }
+
+    /// The map undoing this one, if it is a bijection. That is the case when
+    /// the pieces shuffle the values of their sources between themselves.
+    pub fn inverse(&self) -> Option<Self> {
+        let images = self
+            .pieces
+            .iter()
+            .map(|(src, dst)| *dst..*dst + (src.end - src.start))
+            .collect::<Vec<_>>();
+        // the images add up to the domain's length, so covering it means
+        // they can't overlap
+        if IntervalSet::from_ranges(images.iter().cloned()) != self.domain() {
+            return None;
+        }
+        Some(Self::new(
+            images
+                .into_iter()
+                .zip(self.pieces.iter())
+                .map(|(image, (src, _))| (image, src.start)),
+        ))
+    }
+
+    /// The smallest value the map sends any member of `set` to.
+    ///
+    /// Each translated segment of the set has its smallest image at its start,
+    /// so this only needs to visit the segments rather than the values.
+    pub fn min_image(&self, set: &IntervalSet<T>) -> Option<T> {
+        set.ranges
+            .iter()
+            .flat_map(|r| self.segments(r.clone()))
+            .map(|(_, dst)| dst)
+            .min()
+    }
+
+    /// Split a range into (segment, destination start) pieces over which the
+    /// map is a single translation, including segments it leaves in place.
+    fn segments(&self, range: Range<T>) -> Vec<(Range<T>, T)> {
+        let mut segments = Vec::new();
+        if range.start >= range.end {
+            return segments;
+        }
+        let mut start = range.start;
+        let first = self.pieces.partition_point(|(src, _)| src.end <= start);
+        for (src, dst) in self.pieces[first..].iter() {
+            if src.start >= range.end {
+                break;
+            }
+            if src.start > start {
+                segments.push((start..src.start, start));
+                start = src.start;
+            }
+            let end = src.end.min(range.end);
+            segments.push((start..end, *dst + (start - src.start)));
+            start = end;
+        }
+        if start < range.end {
+            segments.push((start..range.end, start));
+        }
+        segments
+    }
+}
+
+///
+/// Disjoint half-open ranges, each labelled with a value, such as the owner of
+/// the values in the range.
+///
+/// Unlike an [`IntervalSet`], ranges that touch are kept apart, since their
+/// labels may differ.
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IntervalMap<T, V> {
+    /// (range, label), sorted by start.
+    entries: Vec<(Range<T>, V)>,
+}
+
+impl<T, V> Default for IntervalMap<T, V> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<T: Copy + Ord, V> IntervalMap<T, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The (range, label) entries, sorted by start.
+    pub fn entries(&self) -> &[(Range<T>, V)] {
+        &self.entries
+    }
+
+    /// One past the largest labelled value.
+    pub fn end(&self) -> Option<T> {
+        self.entries.last().map(|(r, _)| r.end)
+    }
+
+    /// Label a range. Empty ranges are ignored. Panics if the range overlaps
+    /// one already labelled, as values would then have two labels.
+    pub fn insert(&mut self, range: Range<T>, label: V) {
+        if range.start >= range.end {
+            return;
+        }
+        let idx = self.entries.partition_point(|(r, _)| r.end <= range.start);
+        assert!(
+            self.entries
+                .get(idx)
+                .is_none_or(|(next, _)| range.end <= next.start),
+            "labelled ranges overlap"
+        );
+        self.entries.insert(idx, (range, label));
+    }
+
+    /// The label of the range holding a value.
+    pub fn get(&self, value: &T) -> Option<&V> {
+        let idx = self.entries.partition_point(|(r, _)| r.end <= *value);
+        match self.entries.get(idx) {
+            Some((r, label)) if r.start <= *value => Some(label),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case, clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+    use crate::test_util::XorShift;
+
+    /// Brute force sets are bitmasks over 0..64.
+    fn to_mask(set: &IntervalSet<u64>) -> u64 {
+        set.ranges()
+            .iter()
+            .flat_map(|r| r.clone())
+            .fold(0, |mask, v| mask | (1 << v))
+    }
+
+    fn assert_normalised<T: Ord + Copy + std::fmt::Debug>(set: &IntervalSet<T>) {
+        for r in set.ranges.iter() {
+            assert!(r.start < r.end, "empty range in {set:?}");
+        }
+        for w in set.ranges.windows(2) {
+            assert!(w[0].end < w[1].start, "unmerged ranges in {set:?}");
+        }
+    }
+
+    /// A few random ranges within 0..64, some of them empty.
+    fn ranges(rng: &mut XorShift) -> Vec<Range<u64>> {
+        (0..rng.below(6))
+            .map(|_| {
+                let start = rng.below(64);
+                let end = start + rng.below(65 - start);
+                start..end
+            })
+            .collect()
+    }
+
+    /// A map whose pieces are cut from 0..64 and sent anywhere in 0..164.
+    fn range_map(rng: &mut XorShift) -> RangeMap<u64> {
+        // disjoint source ranges, cut from a random sorted list of points
+        let mut cuts = (0..2 * rng.below(4))
+            .map(|_| rng.below(64))
+            .collect::<Vec<_>>();
+        cuts.sort();
+        let pieces = cuts
+            .chunks(2)
+            .map(|c| (c[0]..c[1], rng.below(100)))
+            .collect::<Vec<_>>();
+        RangeMap::new(pieces)
+    }
+
+    /// A bijection that shuffles blocks of 0..64 around.
+    fn shuffle_map(rng: &mut XorShift) -> RangeMap<u64> {
+        let mut cuts = (0..rng.below(6)).map(|_| rng.below(64)).collect::<Vec<_>>();
+        cuts.extend([0, 64]);
+        cuts.sort();
+        let mut blocks = cuts.windows(2).map(|w| w[0]..w[1]).collect::<Vec<_>>();
+        for i in (1..blocks.len()).rev() {
+            blocks.swap(i, rng.below(i as u64 + 1) as usize);
+        }
+        let mut dst = 0;
+        let pieces = blocks.into_iter().map(|block| {
+            let piece = (block.clone(), dst);
+            dst += block.end - block.start;
+            piece
+        });
+        RangeMap::new(pieces.collect::<Vec<_>>())
+    }
+
+    fn members(set: &IntervalSet<u64>) -> Vec<u64> {
+        set.ranges().iter().flat_map(|r| r.clone()).collect()
+    }
+
+    #[test]
+    fn GIVEN_overlapping_and_touching_ranges_WHEN_building_set_THEN_normalised() {
+        let set = IntervalSet::from_ranges([2..4, 9..12, 2..6, 1..6, 12..14, 20..20]);
+        assert_eq!(set.ranges(), &[1..6, 9..14]);
+        assert_eq!(set.total_length(), 10);
+        assert_eq!(set.start(), Some(1));
+        assert_eq!(set.end(), Some(14));
+        assert!(IntervalSet::<i32>::from_ranges([3..3, 4..4]).is_empty());
+    }
+
+    #[test]
+    fn GIVEN_pairs_of_ranges_WHEN_intersecting_THEN_correct_intersections_produced() {
+        let dotest = |r1: Range<i32>, r2: Range<i32>, expected: &[Range<i32>]| {
+            let (s1, s2) = (IntervalSet::from(r1), IntervalSet::from(r2));
+            assert_eq!(s1.intersection(&s2).ranges(), expected);
+            assert_eq!(s2.intersection(&s1).ranges(), expected);
+        };
+        dotest(-10..0, 2..4, &[]); // no overlap not adjacent
+        dotest(-1..2, 2..4, &[]); // no overlap adjacent
+        dotest(1..3, 2..4, &[2..3]); // overlap r1 < r2
+        dotest(1..3, -2..2, &[1..2]); // overlap r2 < r1
+        dotest(11..23, 15..18, &[15..18]); // r2 contained by r1
+        dotest(11..23, 1..50, &[11..23]); // r1 contained by r2
+    }
+
+    #[test]
+    fn GIVEN_random_sets_WHEN_combining_THEN_matches_bitmask_brute_force() {
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        for _ in 0..2000 {
+            let (ranges_a, ranges_b) = (ranges(&mut rng), ranges(&mut rng));
+            let a = IntervalSet::from_ranges(ranges_a.clone());
+            let b = IntervalSet::from_ranges(ranges_b.clone());
+            let (mask_a, mask_b) = (to_mask(&a), to_mask(&b));
+            let brute_a = ranges_a
+                .iter()
+                .flat_map(|r| r.clone())
+                .fold(0u64, |mask, v| mask | (1 << v));
+            assert_eq!(mask_a, brute_a, "{ranges_a:?}");
+
+            let union = a.union(&b);
+            let intersection = a.intersection(&b);
+            let difference = a.difference(&b);
+            for set in [&a, &union, &intersection, &difference] {
+                assert_normalised(set);
+            }
+            assert_eq!(to_mask(&union), mask_a | mask_b);
+            assert_eq!(to_mask(&intersection), mask_a & mask_b);
+            assert_eq!(to_mask(&difference), mask_a & !mask_b);
+            assert_eq!(a.total_length(), mask_a.count_ones() as u64);
+            assert_eq!(a.is_subset(&b), mask_a & !mask_b == 0);
+
+            let mut inserted = a.clone();
+            for r in ranges_b.iter() {
+                inserted.insert(r.clone());
+                assert_normalised(&inserted);
+            }
+            assert_eq!(inserted, union);
+
+            for v in 0..64 {
+                assert_eq!(a.contains(&v), mask_a & (1 << v) != 0);
+            }
+            let start = rng.below(64);
+            let range = start..start + rng.below(65 - start);
+            let range_mask = range.clone().fold(0u64, |mask, v| mask | (1 << v));
+            assert_eq!(a.contains_range(&range), mask_a & range_mask == range_mask);
+        }
+    }
+
+    #[test]
+    fn GIVEN_pieces_WHEN_mapping_values_THEN_translated_or_identity() {
+        let map = RangeMap::new([(8..13, 30), (2..8, 0)]);
+        assert_eq!(map.map(1), 1);
+        assert_eq!(map.map(2), 0);
+        assert_eq!(map.map(7), 5);
+        assert_eq!(map.map(8), 30);
+        assert_eq!(map.map(12), 34);
+        assert_eq!(map.map(13), 13);
+        assert_eq!(map.domain().ranges(), &[2..13]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn GIVEN_overlapping_pieces_WHEN_building_map_THEN_panics() {
+        RangeMap::new([(0..5, 10), (4..6, 20)]);
+    }
+
+    #[test]
+    fn GIVEN_random_maps_WHEN_mapping_sets_THEN_matches_pointwise_brute_force() {
+        let mut rng = XorShift::new(0x9e3779b97f4a7c15);
+        for _ in 0..1000 {
+            let map = range_map(&mut rng);
+            let set = IntervalSet::from_ranges(ranges(&mut rng));
+
+            let image = map.map_set(&set);
+            assert_normalised(&image);
+            let mut expected = members(&set)
+                .into_iter()
+                .map(|v| map.map(v))
+                .collect::<Vec<_>>();
+            expected.sort();
+            expected.dedup();
+            assert_eq!(members(&image), expected, "{map:?} {set:?}");
+            assert_eq!(map.min_image(&set), expected.first().copied());
+
+            // anything mapping into a set below 64 comes from below 164
+            let preimage = map.preimage(&set);
+            assert_normalised(&preimage);
+            let expected = (0..164)
+                .filter(|v| set.contains(&map.map(*v)))
+                .collect::<Vec<_>>();
+            assert_eq!(members(&preimage), expected, "{map:?} {set:?}");
+        }
+    }
+
+    #[test]
+    fn GIVEN_random_maps_WHEN_composing_THEN_matches_applying_in_turn() {
+        let mut rng = XorShift::new(0xd1b54a32d192ed03);
+        for _ in 0..1000 {
+            let (first, second) = (range_map(&mut rng), range_map(&mut rng));
+            let composed = first.then(&second);
+            for v in 0..200 {
+                assert_eq!(
+                    composed.map(v),
+                    second.map(first.map(v)),
+                    "{first:?} then {second:?} at {v}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn GIVEN_identity_and_joinable_pieces_WHEN_building_map_THEN_normalised() {
+        let map = RangeMap::new([(0..5, 0), (5..8, 20), (8..10, 23), (12..14, 30)]);
+        assert_eq!(map.pieces(), &[(5..10, 20), (12..14, 30)]);
+        // the same joins in a swap of two blocks, which can be undone
+        let map = RangeMap::new([(0..5, 0), (5..8, 10), (8..10, 13), (10..15, 5)]);
+        assert_eq!(map.pieces(), &[(5..10, 10), (10..15, 5)]);
+        let inverse = map.inverse().expect("swapping blocks is a bijection");
+        for value in 5..15 {
+            assert_eq!(value, inverse.map(map.map(value)));
+        }
+        assert_eq!(map.then(&inverse), RangeMap::default());
+    }
+
+    #[test]
+    fn GIVEN_shuffled_blocks_WHEN_inverting_THEN_round_trips() {
+        let mut rng = XorShift::new(0x94d049bb133111eb);
+        for _ in 0..1000 {
+            let map = shuffle_map(&mut rng);
+            let inverse = map.inverse().expect("shuffles are bijections");
+            assert_eq!(map.then(&inverse), RangeMap::default());
+            assert_eq!(inverse.then(&map), RangeMap::default());
+        }
+        // not onto, and then two values landing on 3
+        assert_eq!(RangeMap::new([(0..2, 10)]).inverse(), None);
+        assert_eq!(RangeMap::new([(0..2, 2), (2..4, 3)]).inverse(), None);
+    }
+
+    #[test]
+    fn GIVEN_random_labelled_ranges_WHEN_looking_up_THEN_matches_brute_force() {
+        let mut rng = XorShift::new(0x2545f4914f6cdd1d);
+        for _ in 0..500 {
+            let mut map = IntervalMap::new();
+            let mut labels = [None; 64];
+            // touching ranges with different labels, as from cuts of 0..64
+            let mut cuts = (0..rng.below(8)).map(|_| rng.below(65)).collect::<Vec<_>>();
+            cuts.sort();
+            for (label, w) in cuts.windows(2).enumerate() {
+                if rng.below(3) != 0 {
+                    map.insert(w[0]..w[1], label);
+                    (w[0]..w[1]).for_each(|v| labels[v as usize] = Some(label));
+                }
+            }
+            for value in 0..64 {
+                assert_eq!(labels[value as usize].as_ref(), map.get(&value));
+            }
+            let end = labels
+                .iter()
+                .rposition(Option::is_some)
+                .map(|v| v as u64 + 1);
+            assert_eq!(end, map.end());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "labelled ranges overlap")]
+    fn GIVEN_overlapping_labelled_ranges_WHEN_inserting_THEN_panics() {
+        let mut map = IntervalMap::new();
+        map.insert(4..8, 'a');
+        map.insert(0..5, 'b');
+    }
+}
@@ -0,0 +1,10 @@
+//!
+//! Data structures and algorithms shared between the solutions for different
+//! years.
+//!
+pub mod byte_set;
+pub mod interval;
+pub mod ocr;
+pub mod polygon;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
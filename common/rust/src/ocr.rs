@@ -0,0 +1,156 @@
+//!
+//! Reading the capital letters that some puzzles draw on a grid of pixels.
+//!
+//! The letters use the 4×6 font common to those puzzles, with a dark column
+//! between neighbouring letters. A pixel is lit if it is drawn as `#`; any
+//! other character is dark.
+//!
+use std::fmt;
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+
+/// The columns from the start of one glyph to the start of the next.
+const GLYPH_PITCH: usize = GLYPH_WIDTH + 1;
+
+/// The letters of the font, in the order they are drawn in [`FONT`]. These are
+/// the letters seen in the puzzles so far; any others are reported as unknown
+/// glyphs until they are added here.
+const LETTERS: &str = "ABCEFGHJKLOPRSUZ";
+
+const FONT: [&str; GLYPH_HEIGHT] = [
+    ".##. ###. .##. #### #### .##. #..# ..## #..# #... .##. ###. ###. .### #..# ####",
+    "#..# #..# #..# #... #... #..# #..# ...# #.#. #... #..# #..# #..# #... #..# ...#",
+    "#..# ###. #... ###. ###. #... #### ...# ##.. #... #..# #..# #..# #... #..# ..#.",
+    "#### #..# #... #... #... #.## #..# ...# #.#. #... #..# ###. ###. .##. #..# .#..",
+    "#..# #..# #..# #... #... #..# #..# #..# #.#. #... #..# #... #.#. ...# #..# #...",
+    "#..# ###. .##. #### #... .### #..# .##. #..# #### .##. #... #..# ###. .##. ####",
+];
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum OcrError {
+    /// The picture must be exactly one glyph high.
+    WrongHeight(usize),
+    /// The glyph at the given position, counting from 0, isn't a letter of
+    /// the font. It is given as rows of `#` and `.`.
+    UnknownGlyph { position: usize, glyph: String },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongHeight(rows) => {
+                write!(f, "letters are {GLYPH_HEIGHT} pixels high, not {rows}")
+            }
+            Self::UnknownGlyph { position, glyph } => {
+                write!(f, "glyph {position} isn't a letter:\n{glyph}")
+            }
+        }
+    }
+}
+
+/// The pixels of one glyph as a bitmask, a row of 4 bits at a time.
+fn glyph_bits<S: AsRef<str>>(rows: &[S], position: usize) -> u32 {
+    rows.iter().fold(0, |bits, row| {
+        let pixels = row.as_ref().as_bytes().iter().skip(position * GLYPH_PITCH);
+        let lit = pixels
+            .chain(std::iter::repeat(&b' '))
+            .take(GLYPH_WIDTH)
+            .fold(0, |row_bits, &p| row_bits << 1 | u32::from(p == b'#'));
+        bits << GLYPH_WIDTH | lit
+    })
+}
+
+fn draw_glyph(bits: u32) -> String {
+    (0..GLYPH_HEIGHT)
+        .rev()
+        .map(|row| {
+            (0..GLYPH_WIDTH)
+                .rev()
+                .map(|col| match bits >> (row * GLYPH_WIDTH + col) & 1 {
+                    1 => '#',
+                    _ => '.',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Read the letters drawn across the rows of a picture.
+///
+/// The picture may end with blank columns, which are ignored, but a glyph
+/// that is only partly inside it is read as if the rest were dark.
+pub fn read_letters<S: AsRef<str>>(rows: &[S]) -> Result<String, OcrError> {
+    if rows.len() != GLYPH_HEIGHT {
+        return Err(OcrError::WrongHeight(rows.len()));
+    }
+    let width = rows
+        .iter()
+        .map(|row| row.as_ref().trim_end_matches(|c| c != '#').len())
+        .max()
+        .unwrap_or(0);
+    (0..width.div_ceil(GLYPH_PITCH))
+        .map(|position| {
+            let bits = glyph_bits(rows, position);
+            (0..LETTERS.len())
+                .find(|&letter| glyph_bits(&FONT, letter) == bits)
+                .map(|letter| LETTERS.as_bytes()[letter] as char)
+                .ok_or_else(|| OcrError::UnknownGlyph {
+                    position,
+                    glyph: draw_glyph(bits),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn GIVEN_the_font_itself_WHEN_reading_THEN_every_letter_recognised() {
+        assert_eq!(Ok(LETTERS.to_owned()), read_letters(&FONT));
+        // the same glyphs in spaces and hashes, with a ragged right edge
+        let rows = FONT
+            .iter()
+            .map(|row| row.replace('.', " ").trim_end().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(Ok(LETTERS.to_owned()), read_letters(&rows));
+    }
+
+    #[test]
+    fn GIVEN_letters_with_trailing_blank_columns_WHEN_reading_THEN_blanks_ignored() {
+        let rows = [
+            "###  #### #  #          ",
+            "#  #    # #  #          ",
+            "#  #   #  ####          ",
+            "###   #   #  #          ",
+            "# #  #    #  #          ",
+            "#  # #### #  #          ",
+        ];
+        assert_eq!(Ok("RZH".to_owned()), read_letters(&rows));
+        assert_eq!(Ok(String::new()), read_letters(&[""; GLYPH_HEIGHT]));
+    }
+
+    #[test]
+    fn GIVEN_unreadable_pictures_WHEN_reading_THEN_errors_returned() {
+        assert_eq!(Err(OcrError::WrongHeight(7)), read_letters(&[""; 7]));
+        let rows = [
+            "#### #...",
+            "#..# .#..",
+            "#..# ..#.",
+            "#..# ...#",
+            "#..# ....",
+            "#..# ....",
+        ];
+        assert_eq!(
+            Err(OcrError::UnknownGlyph {
+                position: 0,
+                glyph: rows.map(|row| &row[..GLYPH_WIDTH]).join("\n")
+            }),
+            read_letters(&rows)
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//!
+//! Rectilinear polygons, i.e. those whose edges are all horizontal or vertical,
+//! with integer vertices.
+//!
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RectilinearPolygon {
+    vertices: Vec<(i64, i64)>,
+}
+
+impl RectilinearPolygon {
+    /// Create a polygon from its vertices in order around the perimeter. The
+    /// last vertex connects back to the first.
+    pub fn new(vertices: Vec<(i64, i64)>) -> Self {
+        assert!(vertices.len() >= 4, "too few vertices for a polygon");
+        let polygon = Self { vertices };
+        for ((x0, y0), (x1, y1)) in polygon.edges() {
+            assert!(
+                x0 == x1 || y0 == y1,
+                "edge ({x0}, {y0}) -> ({x1}, {y1}) is not axis-aligned"
+            );
+        }
+        polygon
+    }
+
+    pub fn vertices(&self) -> &[(i64, i64)] {
+        &self.vertices
+    }
+
+    /// Each edge as a pair of (start, end) vertices.
+    pub fn edges(&self) -> impl Iterator<Item = ((i64, i64), (i64, i64))> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    /// The area enclosed by the perimeter, which is positive when the vertices
+    /// run clockwise (with y increasing upwards).
+    pub fn signed_area(&self) -> i64 {
+        // https://en.wikipedia.org/wiki/Shoelace_formula
+        let total: i64 = self
+            .edges()
+            .map(|(prev, xy)| xy.0 * prev.1 - xy.1 * prev.0)
+            .sum();
+        total / 2
+    }
+
+    pub fn area(&self) -> i64 {
+        self.signed_area().abs()
+    }
+}
+
+///
+/// A map of which lattice points lie inside (or on the boundary of) a polygon,
+/// for answering containment queries in constant time.
+///
+/// The distinct vertex x and y coordinates split the plane into a grid of
+/// compressed cells: each vertex coordinate is a cell of its own, as is each
+/// open interval between neighbouring coordinates (and the unbounded intervals
+/// beyond the ends). Every polygon edge runs along vertex coordinates, so each
+/// compressed cell is wholly inside or wholly outside the polygon. A 2D prefix
+/// sum of the outside cells then counts the outside cells within any block.
+/// Cells between coordinates only one apart hold no lattice points, so they are
+/// never counted as outside.
+///
+#[derive(Clone, Debug)]
+pub struct InsideMap {
+    xs: Vec<i64>,
+    ys: Vec<i64>,
+    /// `outside[b][a]` is the number of outside cells with column < a and row < b
+    outside: Vec<Vec<u32>>,
+}
+
+impl InsideMap {
+    pub fn new(polygon: &RectilinearPolygon) -> Self {
+        let mut xs = polygon.vertices.iter().map(|v| v.0).collect::<Vec<_>>();
+        let mut ys = polygon.vertices.iter().map(|v| v.1).collect::<Vec<_>>();
+        xs.sort();
+        xs.dedup();
+        ys.sort();
+        ys.dedup();
+        let ncols = 2 * xs.len() + 1;
+        let nrows = 2 * ys.len() + 1;
+
+        // Cells on the polygon boundary are inside.
+        let mut boundary = vec![vec![false; ncols]; nrows];
+        for ((x0, y0), (x1, y1)) in polygon.edges() {
+            let (a0, a1) = ordered(locate(&xs, x0), locate(&xs, x1));
+            let (b0, b1) = ordered(locate(&ys, y0), locate(&ys, y1));
+            for row in &mut boundary[b0..=b1] {
+                row[a0..=a1].fill(true);
+            }
+        }
+
+        // Cells between vertex coordinates are classified by scanning along each
+        // row counting crossings of vertical edges. Cells on a vertex coordinate
+        // but not on the boundary match the neighbouring in-between cells.
+        let mut inside = vec![vec![false; ncols]; nrows];
+        for b in (2..nrows - 1).step_by(2) {
+            let (ylo, yhi) = (ys[b / 2 - 1], ys[b / 2]);
+            let mut crossings = polygon
+                .edges()
+                .filter(|((x0, y0), (x1, y1))| x0 == x1 && y0.min(y1) <= &ylo && y0.max(y1) >= &yhi)
+                .map(|((x, _), _)| locate(&xs, x))
+                .collect::<Vec<_>>();
+            crossings.sort();
+            for pair in crossings.chunks(2) {
+                if let [start, end] = pair {
+                    inside[b][*start..*end].fill(true);
+                }
+            }
+        }
+        for b in 0..nrows {
+            for a in 0..ncols {
+                inside[b][a] = boundary[b][a] || inside[b & !1][a & !1];
+            }
+        }
+
+        let xs_empty = empty_intervals(&xs);
+        let ys_empty = empty_intervals(&ys);
+        let mut outside = vec![vec![0u32; ncols + 1]; nrows + 1];
+        for b in 0..nrows {
+            for a in 0..ncols {
+                let counted = !inside[b][a] && !xs_empty[a] && !ys_empty[b];
+                outside[b + 1][a + 1] =
+                    outside[b][a + 1] + outside[b + 1][a] - outside[b][a] + u32::from(counted);
+            }
+        }
+        Self { xs, ys, outside }
+    }
+
+    pub fn contains_point(&self, point: (i64, i64)) -> bool {
+        self.contains_rect(point, point)
+    }
+
+    /// Whether every lattice point of the axis-aligned rectangle with the given
+    /// opposite corners, including its edges, lies inside or on the polygon.
+    pub fn contains_rect(&self, corner1: (i64, i64), corner2: (i64, i64)) -> bool {
+        let (a0, a1) = ordered(locate(&self.xs, corner1.0), locate(&self.xs, corner2.0));
+        let (b0, b1) = ordered(locate(&self.ys, corner1.1), locate(&self.ys, corner2.1));
+        let count = self.outside[b1 + 1][a1 + 1] + self.outside[b0][a0]
+            - self.outside[b0][a1 + 1]
+            - self.outside[b1 + 1][a0];
+        count == 0
+    }
+}
+
+/// The compressed cell index of a coordinate: odd for one of the sorted vertex
+/// coordinates, even for the interval before the next one.
+fn locate(coords: &[i64], value: i64) -> usize {
+    match coords.binary_search(&value) {
+        Ok(idx) => 2 * idx + 1,
+        Err(idx) => 2 * idx,
+    }
+}
+
+/// For each compressed cell index, whether it is an interval between two
+/// consecutive coordinates which contains no integers.
+fn empty_intervals(coords: &[i64]) -> Vec<bool> {
+    let mut empty = vec![false; 2 * coords.len() + 1];
+    for (idx, pair) in coords.windows(2).enumerate() {
+        empty[2 * idx + 2] = pair[1] - pair[0] < 2;
+    }
+    empty
+}
+
+fn ordered(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tests {
+    use super::*;
+
+    // The red tiles from AoC 2025 day 9.
+    fn example() -> RectilinearPolygon {
+        RectilinearPolygon::new(vec![
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ])
+    }
+
+    /// Tiles inside the polygon by flood-filling the outside of a bitmap.
+    fn brute_force_tiles(polygon: &RectilinearPolygon) -> Vec<Vec<bool>> {
+        let size = 1 + polygon
+            .vertices()
+            .iter()
+            .map(|v| v.0.max(v.1))
+            .max()
+            .unwrap() as usize;
+        let mut boundary = vec![vec![false; size + 2]; size + 2];
+        for ((x0, y0), (x1, y1)) in polygon.edges() {
+            for x in x0.min(x1)..=x0.max(x1) {
+                for y in y0.min(y1)..=y0.max(y1) {
+                    boundary[y as usize + 1][x as usize + 1] = true;
+                }
+            }
+        }
+        let mut outside = vec![vec![false; size + 2]; size + 2];
+        let mut stack = vec![(0usize, 0usize)];
+        while let Some((x, y)) = stack.pop() {
+            if outside[y][x] || boundary[y][x] {
+                continue;
+            }
+            outside[y][x] = true;
+            if x > 0 {
+                stack.push((x - 1, y));
+            }
+            if y > 0 {
+                stack.push((x, y - 1));
+            }
+            if x < size + 1 {
+                stack.push((x + 1, y));
+            }
+            if y < size + 1 {
+                stack.push((x, y + 1));
+            }
+        }
+        (0..size)
+            .map(|y| (0..size).map(|x| !outside[y + 1][x + 1]).collect())
+            .collect()
+    }
+
+    #[test]
+    fn GIVEN_squares_WHEN_calcing_area_THEN_correct_answer_returned() {
+        let square = RectilinearPolygon::new(vec![(0, 0), (0, 3), (3, 3), (3, 0)]);
+        assert_eq!(9, square.signed_area());
+        let square = RectilinearPolygon::new(vec![(2, 2), (2, -2), (-2, -2), (-2, 2)]);
+        assert_eq!(16, square.signed_area());
+        let square = RectilinearPolygon::new(vec![(0, 0), (3, 0), (3, 3), (0, 3)]);
+        assert_eq!(-9, square.signed_area());
+        assert_eq!(9, square.area());
+        assert_eq!(30, example().area());
+    }
+
+    #[test]
+    #[should_panic]
+    fn GIVEN_diagonal_edge_WHEN_constructing_polygon_THEN_panics() {
+        RectilinearPolygon::new(vec![(0, 0), (0, 3), (3, 3), (3, 1), (1, 0)]);
+    }
+
+    fn u_shape(gap: i64) -> RectilinearPolygon {
+        RectilinearPolygon::new(vec![
+            (0, 0),
+            (4 + gap, 0),
+            (4 + gap, 4),
+            (2 + gap, 4),
+            (2 + gap, 1),
+            (2, 1),
+            (2, 4),
+            (0, 4),
+        ])
+    }
+
+    fn spiral() -> RectilinearPolygon {
+        RectilinearPolygon::new(vec![
+            (1, 1),
+            (9, 1),
+            (9, 8),
+            (3, 8),
+            (3, 4),
+            (6, 4),
+            (6, 6),
+            (7, 6),
+            (7, 3),
+            (2, 3),
+            (2, 9),
+            (1, 9),
+        ])
+    }
+
+    #[test]
+    fn GIVEN_example_polygons_WHEN_querying_points_THEN_matches_flood_fill() {
+        for polygon in [example(), u_shape(1), u_shape(2), spiral()] {
+            let map = InsideMap::new(&polygon);
+            let tiles = brute_force_tiles(&polygon);
+            for (y, row) in tiles.iter().enumerate() {
+                for (x, &expected) in row.iter().enumerate() {
+                    assert_eq!(
+                        expected,
+                        map.contains_point((x as i64, y as i64)),
+                        "({x}, {y}) in {polygon:?}"
+                    );
+                }
+            }
+            assert!(!map.contains_point((-5, 3)));
+            assert!(!map.contains_point((4, 100)));
+        }
+    }
+
+    #[test]
+    fn GIVEN_example_polygons_WHEN_querying_rectangles_THEN_matches_flood_fill() {
+        for polygon in [example(), u_shape(1), u_shape(2), spiral()] {
+            let map = InsideMap::new(&polygon);
+            let tiles = brute_force_tiles(&polygon);
+            let size = tiles.len() as i64;
+            let brute_force = |(x0, y0): (i64, i64), (x1, y1): (i64, i64)| {
+                (y0.min(y1)..=y0.max(y1))
+                    .all(|y| (x0.min(x1)..=x0.max(x1)).all(|x| tiles[y as usize][x as usize]))
+            };
+            for x0 in 0..size {
+                for y0 in 0..size {
+                    for x1 in x0..size {
+                        for y1 in 0..size {
+                            assert_eq!(
+                                brute_force((x0, y0), (x1, y1)),
+                                map.contains_rect((x0, y0), (x1, y1)),
+                                "({x0}, {y0}) - ({x1}, {y1}) in {polygon:?}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn GIVEN_u_shaped_polygon_WHEN_querying_across_slot_THEN_only_tiles_count() {
+        // arms one apart leave no tiles between them, two apart leave one
+        let touching = InsideMap::new(&u_shape(1));
+        assert!(touching.contains_rect((0, 2), (5, 2)));
+        let gapped = InsideMap::new(&u_shape(2));
+        assert!(!gapped.contains_rect((0, 2), (6, 2)));
+        assert!(!gapped.contains_point((3, 2)));
+        assert!(gapped.contains_rect((0, 0), (6, 1)));
+        assert!(gapped.contains_rect((4, 1), (6, 4)));
+    }
+}
@@ -0,0 +1,27 @@
+//!
+//! Helpers for tests, shared with the years' crates through the `test-util`
+//! feature.
+//!
+
+/// A seeded xorshift generator, so that randomised tests are reproducible
+/// without a rand dependency.
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        assert!(seed != 0, "xorshift never leaves a zero state");
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..limit`.
+    pub fn below(&mut self, limit: u64) -> u64 {
+        self.next_u64() % limit
+    }
+}